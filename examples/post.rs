@@ -1,15 +1,14 @@
 use async_curl::CurlActor;
 use curl_http_client::*;
-use http::{Method, Request};
+use http::Method;
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let actor = CurlActor::new();
     let collector = Collector::Ram(Vec::new());
 
-    let request = Request::builder()
-        .uri("<TARGET URL>")
-        .method(Method::POST)
+    let request = request_builder(Method::POST, "<TARGET URL>")
+        .headers(&[("Content-Type", "text/plain"), ("Accept", "text/plain")])
         .body(Some("test body".as_bytes().to_vec()))
         .unwrap();
 
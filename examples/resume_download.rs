@@ -1,4 +1,3 @@
-use std::fs;
 use std::path::PathBuf;
 
 use async_curl::CurlActor;
@@ -11,7 +10,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let save_to = PathBuf::from("<FILE PATH TO SAVE>");
     let collector = Collector::File(FileInfo::path(save_to.clone()));
 
-    let partial_download_file_size = fs::metadata(save_to.as_path())?.len() as usize;
+    let offset = FileInfo::current_offset(&save_to)?;
     let request = Request::builder()
         .uri("<SOURCE URL>")
         .method(Method::GET)
@@ -19,7 +18,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .unwrap();
 
     let response = HttpClient::new(collector)
-        .resume_from(BytesOffset::from(partial_download_file_size))
+        .resume_from(offset)
         .unwrap()
         .request(request)
         .unwrap()
@@ -1,18 +1,27 @@
+use std::collections::VecDeque;
 use std::fmt::Debug;
 use std::io::Read;
+use std::sync::mpsc::{sync_channel, SyncSender};
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 use std::{
     fs::{File, OpenOptions},
     io::{Seek, SeekFrom, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
-use curl::easy::{Handler, ReadError, WriteError};
+use curl::easy::{Handler, InfoType, ReadError, SeekResult, WriteError};
 use derive_deref_rs::Deref;
 use http::{HeaderMap, HeaderName, HeaderValue};
 use log::trace;
-use tokio::sync::mpsc::Sender;
+use tokio::sync::{
+    mpsc::{Sender, UnboundedSender},
+    watch,
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::{BytesOffset, HarRecord};
 
 /// This is an information about the transfer(Download/Upload) speed that will be sent across tasks.
 /// It is useful to get the transfer speed and displayed it according to
@@ -78,6 +87,16 @@ impl Default for AbortPerform {
     }
 }
 
+/// A function shared across threads that is called with the transfer progress of a FileInfo.
+#[derive(Clone)]
+struct ProgressCallback(Arc<Mutex<dyn FnMut(TransferSpeed, usize) -> bool + Send>>);
+
+impl Debug for ProgressCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProgressCallback").finish_non_exhaustive()
+    }
+}
+
 /// Stores the path for the downloaded file or the uploaded file.
 /// Internally it will also monitor the bytes transferred and the Download/Upload speed.
 #[derive(Clone, Debug)]
@@ -87,10 +106,26 @@ pub struct FileInfo {
     /// Sends the transfer speed information via channel to another task.
     /// This is an optional parameter depends on the user application.
     send_speed_info: Option<Sender<TransferSpeed>>,
+    /// Publishes the latest transfer speed information to another task.
+    watch_speed_info: Option<Arc<watch::Sender<TransferSpeed>>>,
     bytes_transferred: usize,
     transfer_started: Instant,
+    /// The bytes transferred within the last `SPEED_WINDOW` and when they were transferred.
+    speed_samples: VecDeque<(Instant, usize)>,
     transfer_speed: TransferSpeed,
     abort: Option<AbortPerform>,
+    cancellation_token: Option<CancellationToken>,
+    write_offset: Option<u64>,
+    delete_on_error: bool,
+    fsync_on_complete: bool,
+    atomic_rename: bool,
+    /// Set once the file is read to be uploaded, it is then not replaced by the temporary file
+    /// of `with_atomic_rename`.
+    uploaded: bool,
+    store_compressed: bool,
+    progress_callback: Option<ProgressCallback>,
+    /// The header lines received when the file is downloaded with Collector::File(FileInfo).
+    headers: Vec<u8>,
 }
 
 impl FileInfo {
@@ -99,20 +134,56 @@ impl FileInfo {
         Self {
             path,
             send_speed_info: None,
+            watch_speed_info: None,
             bytes_transferred: 0,
             transfer_started: Instant::now(),
+            speed_samples: VecDeque::new(),
             transfer_speed: TransferSpeed::from(0),
             abort: None,
+            cancellation_token: None,
+            write_offset: None,
+            delete_on_error: false,
+            fsync_on_complete: false,
+            atomic_rename: false,
+            uploaded: false,
+            store_compressed: false,
+            progress_callback: None,
+            headers: Vec::new(),
+        }
+    }
+
+    /// Returns the size of the partially downloaded file as the offset to resume the download from,
+    /// e.g. `resume_from(FileInfo::current_offset(&path)?)`.
+    ///
+    /// A file that does not exist yet has nothing downloaded, so the offset is 0.
+    pub fn current_offset<P: AsRef<Path>>(path: P) -> std::io::Result<BytesOffset> {
+        match std::fs::metadata(path) {
+            Ok(metadata) => Ok(BytesOffset::from(metadata.len())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BytesOffset::from(0u64)),
+            Err(e) => Err(e),
         }
     }
 
     /// Sets the FileInfo struct with a message passing channel to send transfer speed information across user applications.
     /// It uses a tokio bounded channel to send the information across tasks.
+    /// The information is sent without a tokio runtime, so this can also be used with `HttpClient::blocking`.
+    /// An update is dropped when the channel is full.
     pub fn with_transfer_speed_sender(mut self, send_speed_info: Sender<TransferSpeed>) -> Self {
         self.send_speed_info = Some(send_speed_info);
         self
     }
 
+    /// Sets the FileInfo struct with a watch channel to publish transfer speed information across user applications.
+    /// Unlike `with_transfer_speed_sender`, a newer speed replaces the one that has not been seen yet, so the
+    /// receiver always gets the most recent speed without a backlog of old ones.
+    pub fn with_transfer_speed_watch(
+        mut self,
+        watch_speed_info: watch::Sender<TransferSpeed>,
+    ) -> Self {
+        self.watch_speed_info = Some(Arc::new(watch_speed_info));
+        self
+    }
+
     /// Set the FileInfo struct with a perform aborter.
     /// AbortPerform is a shared flag across threads to be able to switch this flag to true to abort the curl perform.
     pub fn with_perform_aborter(mut self, abort: AbortPerform) -> Self {
@@ -120,36 +191,229 @@ impl FileInfo {
         self
     }
 
+    /// Set the FileInfo struct with a cancellation token that aborts the curl perform once it is cancelled,
+    /// e.g. a child token of the task that is shut down. The perform then fails with `Error::Aborted`.
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancellation_token
+            .as_ref()
+            .map(CancellationToken::is_cancelled)
+            .unwrap_or(false)
+    }
+
+    /// Set the FileInfo struct with a function that is called with the transfer speed and the number of
+    /// bytes transferred so far whenever data is written to or read from the file.
+    /// Returning false from the function aborts the transfer, e.g. once an upload exceeds a size.
+    ///
+    /// The function is called from the thread performing the transfer, like the actor, so it must be `Send`.
+    pub fn with_progress_callback<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(TransferSpeed, usize) -> bool + Send + 'static,
+    {
+        self.progress_callback = Some(ProgressCallback(Arc::new(Mutex::new(callback))));
+        self
+    }
+
+    /// Removes the partially downloaded file if the transfer fails.
+    /// A file that already exists before the transfer, like when resuming a download, is never removed.
+    pub fn delete_on_error(mut self, delete: bool) -> Self {
+        self.delete_on_error = delete;
+        self
+    }
+
+    /// Stores the response body in the file as it was sent when it is compressed with `HttpClient::accept_encoding`,
+    /// e.g. to keep a `.gz` file as is. The `Content-Encoding` header of the response then describes the encoding
+    /// of the file. By default the body is decompressed before it is written and the file holds the original content
+    /// even though the `Content-Encoding` header still names the compression.
+    pub fn store_compressed(mut self, store_compressed: bool) -> Self {
+        self.store_compressed = store_compressed;
+        self
+    }
+
+    /// Flushes the downloaded file to the storage device with `File::sync_all` once the transfer
+    /// has completed, so the data is not lost if the system crashes right after perform.
+    pub fn fsync_on_complete(mut self, fsync: bool) -> Self {
+        self.fsync_on_complete = fsync;
+        self
+    }
+
+    /// Downloads to the temporary file `{path}.part` and renames it to the path once the transfer
+    /// has completed, so the path only ever holds a complete download. The temporary file is removed
+    /// if the transfer fails.
+    ///
+    /// To resume an interrupted download, the temporary file is the one to resume from, e.g.
+    /// `resume_from(FileInfo::current_offset(FileInfo::part_path(&path))?)`. A temporary file that
+    /// already exists before the transfer is kept if it fails again.
+    ///
+    /// This only applies to downloads, a file that is uploaded is left as it is.
+    pub fn with_atomic_rename(mut self, atomic_rename: bool) -> Self {
+        self.atomic_rename = atomic_rename;
+        self
+    }
+
+    /// Returns the path of the temporary file that is written with `with_atomic_rename`, which is the
+    /// path with `.part` appended, e.g. `image.iso.part`.
+    pub fn part_path<P: AsRef<Path>>(path: P) -> PathBuf {
+        let mut part = path.as_ref().as_os_str().to_os_string();
+        part.push(".part");
+        PathBuf::from(part)
+    }
+
+    /// Returns the path of the file the received data is written to.
+    fn write_path(&self) -> PathBuf {
+        if self.writes_part() {
+            Self::part_path(&self.path)
+        } else {
+            self.path.clone()
+        }
+    }
+
+    /// Moves the temporary file of `with_atomic_rename` to the path.
+    fn rename_part(&self) -> std::io::Result<()> {
+        if self.writes_part() {
+            std::fs::rename(self.write_path(), &self.path)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns true if the received data goes to the temporary file of `with_atomic_rename`.
+    fn writes_part(&self) -> bool {
+        self.atomic_rename && !self.uploaded
+    }
+
+    /// Writes the received data starting at the given offset of the file instead of appending it.
+    /// This is used when several ranges of the same file are downloaded concurrently.
+    pub(crate) fn at_offset(mut self, offset: u64) -> Self {
+        self.write_offset = Some(offset);
+        self
+    }
+
+    /// Creates the empty file if nothing was written to it, e.g. the response was 204 No Content.
+    fn create_if_missing(&self) -> std::io::Result<()> {
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(self.write_path())
+        {
+            Ok(_) => Ok(()),
+            // The file was downloaded or it is the one that was uploaded.
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn sync_file(&self) -> std::io::Result<()> {
+        OpenOptions::new()
+            .write(true)
+            .open(self.write_path())?
+            .sync_all()
+    }
+
+    fn open_for_write(&self) -> std::io::Result<File> {
+        match self.write_offset {
+            Some(offset) => {
+                let mut file = OpenOptions::new()
+                    .create(true)
+                    .truncate(false)
+                    .write(true)
+                    .open(self.write_path())?;
+                file.seek(SeekFrom::Start(offset + self.bytes_transferred as u64))?;
+                Ok(file)
+            }
+            None => OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.write_path()),
+        }
+    }
+
+    /// Removes the data written by the current response from the file, so the next response
+    /// starts where this one started.
+    fn discard_written(&mut self) -> std::io::Result<()> {
+        if self.bytes_transferred > 0 && self.write_offset.is_none() {
+            let file = OpenOptions::new().write(true).open(self.write_path())?;
+            let length = file.metadata()?.len();
+            file.set_len(length.saturating_sub(self.bytes_transferred as u64))?;
+        }
+        // With a write offset, the next response is written from the offset again.
+        self.bytes_transferred = 0;
+        Ok(())
+    }
+
     fn update_bytes_transferred(&mut self, transferred: usize) {
+        self.update_bytes_transferred_at(transferred, Instant::now());
+    }
+
+    /// Computes the transfer speed from the bytes transferred within the last `SPEED_WINDOW`,
+    /// so the speed follows bursts and stalls instead of the average since the start.
+    pub(crate) fn update_bytes_transferred_at(&mut self, transferred: usize, now: Instant) {
         self.bytes_transferred += transferred;
 
-        let now = Instant::now();
-        let difference = now.duration_since(self.transfer_started);
+        self.speed_samples.push_back((now, transferred));
+        while let Some(&(time, _)) = self.speed_samples.front() {
+            if now.duration_since(time) > SPEED_WINDOW {
+                self.speed_samples.pop_front();
+            } else {
+                break;
+            }
+        }
 
-        self.transfer_speed =
-            TransferSpeed::from((self.bytes_transferred) as f64 / difference.as_secs_f64());
+        // The window is shorter until the transfer has run for a whole window. The first sample
+        // may arrive right at the start, the speed is kept until time has passed.
+        let window = now.duration_since(self.transfer_started).min(SPEED_WINDOW);
+        if window.is_zero() {
+            return;
+        }
+        let bytes: usize = self.speed_samples.iter().map(|(_, bytes)| bytes).sum();
+        self.transfer_speed = TransferSpeed::from(bytes as f64 / window.as_secs_f64());
     }
 
     fn bytes_transferred(&self) -> usize {
         self.bytes_transferred
     }
 
-    fn transfer_speed(&self) -> TransferSpeed {
+    /// Calls the progress callback, returns false if the transfer must be aborted.
+    fn report_progress(&self) -> bool {
+        match &self.progress_callback {
+            Some(callback) => {
+                let mut callback = callback.0.lock().unwrap();
+                (*callback)(self.transfer_speed(), self.bytes_transferred)
+            }
+            None => true,
+        }
+    }
+
+    pub(crate) fn transfer_speed(&self) -> TransferSpeed {
         self.transfer_speed.clone()
     }
 }
 
+/// The period over which the transfer speed of a FileInfo is computed.
+const SPEED_WINDOW: Duration = Duration::from_secs(3);
+
 fn send_transfer_info(info: &FileInfo) {
-    if let Some(tx) = info.send_speed_info.clone() {
-        let transfer_speed = info.transfer_speed();
-        tokio::spawn(async move {
-            tx.send(transfer_speed).await.map_err(|e| {
-                trace!("{:?}", e);
-            })
+    if let Some(tx) = &info.watch_speed_info {
+        // The value is replaced even if there is no receiver at the moment.
+        tx.send_replace(info.transfer_speed());
+    }
+    if let Some(tx) = &info.send_speed_info {
+        // Sending without awaiting does not need a tokio runtime, so this also works with `blocking()`.
+        // An update is dropped if the channel is full, the next one carries the newer speed.
+        let _ = tx.try_send(info.transfer_speed()).map_err(|e| {
+            trace!("{:?}", e);
         });
     }
 }
 
+/// The length that `Handler::write` returns to abort the transfer. curl fails the transfer with
+/// `CURLE_WRITE_ERROR` as soon as the returned length is less than the length of the received data.
+const ABORT_WRITE: usize = 0;
+
 /// This is an extended trait for the curl::easy::Handler trait.
 pub trait ExtendedHandler: Handler {
     // Return the response body if the Collector is available.
@@ -160,12 +424,151 @@ pub trait ExtendedHandler: Handler {
     fn get_response_body_and_headers(&self) -> (Option<Vec<u8>>, Option<HeaderMap>) {
         (None, None)
     }
+    // Return the maximum size of the response body if the Collector is capped.
+    fn max_body_size(&self) -> Option<usize> {
+        None
+    }
+    // Return the path of the file to be removed if the transfer fails.
+    fn remove_on_error(&self) -> Option<PathBuf> {
+        None
+    }
+    // Return the size of the data to be uploaded if the Collector knows it.
+    fn upload_size(&self) -> Option<u64> {
+        None
+    }
+    // Called once the transfer has completed successfully.
+    fn transfer_complete(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+    // Return the flag that aborts the transfer through the progress function if the Collector
+    // supports it, a new one is set if the Collector does not have one yet.
+    fn perform_aborter(&mut self) -> Option<AbortPerform> {
+        None
+    }
+    // Return the record of the request and the response if the Collector records them.
+    fn har_record(&self) -> Option<HarRecord> {
+        None
+    }
+    // Return true if the Collector wants the response body as it was sent, without decoding its Content-Encoding.
+    fn store_compressed(&self) -> bool {
+        false
+    }
+    // Return the status line of the final response, e.g. `HTTP/1.1 418 I'm a teapot`, if the Collector keeps the headers.
+    fn status_line(&self) -> Option<String> {
+        None
+    }
+    // Return the token that aborts the transfer once it is cancelled if the Collector has one.
+    fn cancellation_token(&self) -> Option<CancellationToken> {
+        None
+    }
+    // Return the trailers that were received after the response body if the Collector keeps the headers.
+    fn trailers(&self) -> Option<HeaderMap> {
+        None
+    }
+    // Drop the body and the headers of a redirect response before the redirect is followed by this crate.
+    fn discard_response(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Implements `ExtendedHandler` for a collector that wraps another one in its `collector` field.
+///
+/// Every method is forwarded to the wrapped collector, so a method added to the trait has to be added
+/// here once instead of to every wrapper. A wrapper that has its own abort flag, cancellation token or
+/// record provides the expression for `perform_aborter`, `cancellation_token` or `har_record` with a
+/// name for `self`, e.g. `perform_aborter(wrapper) => Some(wrapper.abort.clone())`.
+/// Returning its own abort flag enables the progress function of the wrapper, the flag of the wrapped
+/// collector is still checked by the progress function of the wrapped collector.
+macro_rules! forward_extended_handler {
+    (
+        impl[$($generics:tt)*] $wrapper:ty where [$($bounds:tt)*]
+        $(; perform_aborter($aborter_self:ident) => $perform_aborter:expr)?
+        $(; cancellation_token($token_self:ident) => $cancellation_token:expr)?
+        $(; har_record($record_self:ident) => $har_record:expr)?
+        $(;)?
+    ) => {
+        impl<$($generics)*> $crate::ExtendedHandler for $wrapper
+        where
+            $($bounds)*
+        {
+            fn get_response_body(&self) -> Option<Vec<u8>> {
+                self.collector.get_response_body()
+            }
+
+            fn get_response_body_and_headers(&self) -> (Option<Vec<u8>>, Option<::http::HeaderMap>) {
+                self.collector.get_response_body_and_headers()
+            }
+
+            fn max_body_size(&self) -> Option<usize> {
+                self.collector.max_body_size()
+            }
+
+            fn remove_on_error(&self) -> Option<::std::path::PathBuf> {
+                self.collector.remove_on_error()
+            }
+
+            fn upload_size(&self) -> Option<u64> {
+                self.collector.upload_size()
+            }
+
+            fn transfer_complete(&mut self) -> ::std::io::Result<()> {
+                self.collector.transfer_complete()
+            }
+
+            fn perform_aborter(&mut self) -> Option<$crate::AbortPerform> {
+                $crate::collector::forward_extended_handler!(
+                    @own self, self.collector.perform_aborter()
+                    $(, $aborter_self => $perform_aborter)?
+                )
+            }
+
+            fn har_record(&self) -> Option<$crate::HarRecord> {
+                $crate::collector::forward_extended_handler!(
+                    @own self, self.collector.har_record()
+                    $(, $record_self => $har_record)?
+                )
+            }
+
+            fn store_compressed(&self) -> bool {
+                self.collector.store_compressed()
+            }
+
+            fn status_line(&self) -> Option<String> {
+                self.collector.status_line()
+            }
+
+            fn cancellation_token(&self) -> Option<::tokio_util::sync::CancellationToken> {
+                $crate::collector::forward_extended_handler!(
+                    @own self, self.collector.cancellation_token()
+                    $(, $token_self => $cancellation_token)?
+                )
+            }
+
+            fn trailers(&self) -> Option<::http::HeaderMap> {
+                self.collector.trailers()
+            }
+
+            fn discard_response(&mut self) -> ::std::io::Result<()> {
+                self.collector.discard_response()
+            }
+        }
+    };
+    (@own $self:ident, $forwarded:expr) => {
+        $forwarded
+    };
+    (@own $self:ident, $forwarded:expr, $own_self:ident => $own:expr) => {{
+        let $own_self = $self;
+        $own
+    }};
 }
+pub(crate) use forward_extended_handler;
 
 /// Collector::File(FileInfo) is used to be able to download and upload files.
 /// Collector::Ram(`Vec<u8>`) is used to store response body into Memory.
 /// Collector::RamWithHeaders(`Vec<u8>`, `Vec<u8>`) is used to store response body into Memory and with complete headers.
 /// Collector::FileAndHeaders(`FileInfo`, `Vec<u8>`) is used to be able to download and upload files and with complete headers.
+/// Collector::RamCapped(`Vec<u8>`, `usize`) is used to store response body into Memory up to a maximum size.
+/// Collector::RamAndHeadersCapped(`Vec<u8>`, `Vec<u8>`, `HeaderLimit`) is used to store response body into Memory and with headers up to a maximum size.
 #[derive(Clone, Debug)]
 pub enum Collector {
     /// Collector::File(`FileInfo`) is used to be able to download and upload files.
@@ -176,6 +579,77 @@ pub enum Collector {
     RamAndHeaders(Vec<u8>, Vec<u8>),
     /// Collector::FileAndHeaders(`FileInfo`, `Vec<u8>`) is used to be able to download and upload files and with complete headers.
     FileAndHeaders(FileInfo, Vec<u8>),
+    /// Collector::RamCapped(`Vec<u8>`, `usize`) is used to store response body into Memory up to a maximum size in bytes.
+    /// The transfer is aborted with [`Error::BodyTooLarge`](crate::Error::BodyTooLarge) once the response body exceeds it.
+    RamCapped(Vec<u8>, usize),
+    /// Collector::RamAndHeadersCapped(`Vec<u8>`, `Vec<u8>`, `HeaderLimit`) is used to store response body into Memory and with
+    /// headers up to the maximum size of the [`HeaderLimit`], which protects against servers sending endless headers.
+    RamAndHeadersCapped(Vec<u8>, Vec<u8>, HeaderLimit),
+}
+
+/// HeaderLimit is the maximum size in bytes of the headers stored by Collector::RamAndHeadersCapped.
+///
+/// Only complete header lines are stored, a line that does not fit is not appended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HeaderLimit {
+    max_size: usize,
+    abort: bool,
+}
+
+impl HeaderLimit {
+    /// The header lines that exceed the maximum size are dropped and the transfer continues.
+    pub fn truncate(max_size: usize) -> Self {
+        Self {
+            max_size,
+            abort: false,
+        }
+    }
+
+    /// The transfer is aborted once the headers exceed the maximum size, `perform` fails with
+    /// `Error::Perform` holding curl's write error.
+    pub fn abort(max_size: usize) -> Self {
+        Self {
+            max_size,
+            abort: true,
+        }
+    }
+
+    /// Returns the maximum size of the headers in bytes.
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+}
+
+impl Collector {
+    /// Creates a Collector::Ram(`Vec<u8>`) with room for a response body of the given size in bytes,
+    /// so it does not have to be reallocated while it is received.
+    pub fn ram_with_capacity(body_capacity: usize) -> Self {
+        Collector::Ram(Vec::with_capacity(body_capacity))
+    }
+
+    /// Creates a Collector::RamAndHeaders(`Vec<u8>`, `Vec<u8>`) with room for a response body and
+    /// headers of the given sizes in bytes, so they do not have to be reallocated while they are received.
+    pub fn ram_and_headers_with_capacity(body_capacity: usize, header_capacity: usize) -> Self {
+        Collector::RamAndHeaders(
+            Vec::with_capacity(body_capacity),
+            Vec::with_capacity(header_capacity),
+        )
+    }
+
+    /// Empties the response body and the headers that were received, so the Collector can receive the
+    /// next response. The body of a file is kept, the next body is appended to it.
+    pub(crate) fn clear_response(&mut self) {
+        match self {
+            Collector::File(info) => info.headers.clear(),
+            Collector::FileAndHeaders(_, headers) => headers.clear(),
+            Collector::Ram(container) | Collector::RamCapped(container, _) => container.clear(),
+            Collector::RamAndHeaders(container, headers)
+            | Collector::RamAndHeadersCapped(container, headers, _) => {
+                container.clear();
+                headers.clear();
+            }
+        }
+    }
 }
 
 impl Handler for Collector {
@@ -185,14 +659,13 @@ impl Handler for Collector {
     fn write(&mut self, data: &[u8]) -> Result<usize, WriteError> {
         match self {
             Collector::File(info) => {
-                let mut file = OpenOptions::new()
-                    .create(true)
-                    .append(true)
-                    .open(info.path.clone())
-                    .map_err(|e| {
-                        trace!("{}", e);
-                        WriteError::Pause
-                    })?;
+                if info.is_cancelled() {
+                    return Ok(ABORT_WRITE);
+                }
+                let mut file = info.open_for_write().map_err(|e| {
+                    trace!("{}", e);
+                    WriteError::Pause
+                })?;
 
                 file.write_all(data).map_err(|e| {
                     trace!("{}", e);
@@ -202,25 +675,39 @@ impl Handler for Collector {
                 info.update_bytes_transferred(data.len());
 
                 send_transfer_info(info);
+                if !info.report_progress() {
+                    return Ok(ABORT_WRITE);
+                }
                 Ok(data.len())
             }
             Collector::Ram(container) => {
                 container.extend_from_slice(data);
                 Ok(data.len())
             }
-            Collector::RamAndHeaders(container, _) => {
+            Collector::RamAndHeaders(container, _)
+            | Collector::RamAndHeadersCapped(container, _, _) => {
+                container.extend_from_slice(data);
+                Ok(data.len())
+            }
+            Collector::RamCapped(container, max_size) => {
+                if container.len() + data.len() > *max_size {
+                    trace!(
+                        "Response body exceeded the maximum size of {} bytes",
+                        max_size
+                    );
+                    return Ok(ABORT_WRITE);
+                }
                 container.extend_from_slice(data);
                 Ok(data.len())
             }
             Collector::FileAndHeaders(info, _) => {
-                let mut file = OpenOptions::new()
-                    .create(true)
-                    .append(true)
-                    .open(info.path.clone())
-                    .map_err(|e| {
-                        trace!("{}", e);
-                        WriteError::Pause
-                    })?;
+                if info.is_cancelled() {
+                    return Ok(ABORT_WRITE);
+                }
+                let mut file = info.open_for_write().map_err(|e| {
+                    trace!("{}", e);
+                    WriteError::Pause
+                })?;
 
                 file.write_all(data).map_err(|e| {
                     trace!("{}", e);
@@ -230,6 +717,9 @@ impl Handler for Collector {
                 info.update_bytes_transferred(data.len());
 
                 send_transfer_info(info);
+                if !info.report_progress() {
+                    return Ok(ABORT_WRITE);
+                }
                 Ok(data.len())
             }
         }
@@ -239,6 +729,7 @@ impl Handler for Collector {
     fn read(&mut self, data: &mut [u8]) -> Result<usize, ReadError> {
         match self {
             Collector::File(info) => {
+                info.uploaded = true;
                 let mut file = File::open(info.path.clone()).map_err(|e| {
                     trace!("{}", e);
                     ReadError::Abort
@@ -258,10 +749,15 @@ impl Handler for Collector {
                 info.update_bytes_transferred(read_size);
 
                 send_transfer_info(info);
+                if !info.report_progress() {
+                    return Err(ReadError::Abort);
+                }
                 Ok(read_size)
             }
             Collector::Ram(_) => Ok(0),
             Collector::RamAndHeaders(_, _) => Ok(0),
+            Collector::RamCapped(_, _) => Ok(0),
+            Collector::RamAndHeadersCapped(_, _, _) => Ok(0),
             Collector::FileAndHeaders(info, _) => {
                 let mut file = File::open(info.path.clone()).map_err(|e| {
                     trace!("{}", e);
@@ -282,6 +778,9 @@ impl Handler for Collector {
                 info.update_bytes_transferred(read_size);
 
                 send_transfer_info(info);
+                if !info.report_progress() {
+                    return Err(ReadError::Abort);
+                }
                 Ok(read_size)
             }
         }
@@ -289,14 +788,28 @@ impl Handler for Collector {
 
     fn header(&mut self, data: &[u8]) -> bool {
         match self {
-            Collector::File(_) => {}
+            Collector::File(info) => {
+                info.headers.extend_from_slice(data);
+            }
             Collector::Ram(_) => {}
+            Collector::RamCapped(_, _) => {}
             Collector::RamAndHeaders(_, headers) => {
                 headers.extend_from_slice(data);
             }
             Collector::FileAndHeaders(_, headers) => {
                 headers.extend_from_slice(data);
             }
+            Collector::RamAndHeadersCapped(_, headers, limit) => {
+                if headers.len() + data.len() > limit.max_size {
+                    trace!(
+                        "Response headers exceeded the maximum size of {} bytes",
+                        limit.max_size
+                    );
+                    // Returning false makes curl abort the transfer.
+                    return !limit.abort;
+                }
+                headers.extend_from_slice(data);
+            }
         }
         true
     }
@@ -305,6 +818,10 @@ impl Handler for Collector {
         trace!("dltotal: {dltotal} dlnow: {dlnow} ultotal: {ultotal} ulnow: {ulnow}");
         match self {
             Collector::File(file_info) | Collector::FileAndHeaders(file_info, _) => {
+                if file_info.is_cancelled() {
+                    trace!("Transfer was cancelled");
+                    return false;
+                }
                 if let Some(abort) = &file_info.abort {
                     let abort = *abort.lock().unwrap();
                     !abort
@@ -312,7 +829,10 @@ impl Handler for Collector {
                     true
                 }
             }
-            Collector::Ram(_) | Collector::RamAndHeaders(_, _) => true,
+            Collector::Ram(_)
+            | Collector::RamAndHeaders(_, _)
+            | Collector::RamCapped(_, _)
+            | Collector::RamAndHeadersCapped(_, _, _) => true,
         }
     }
 }
@@ -325,14 +845,15 @@ impl ExtendedHandler for Collector {
     fn get_response_body(&self) -> Option<Vec<u8>> {
         match self {
             Collector::File(_) => None,
-            Collector::Ram(container) => {
+            Collector::Ram(container) | Collector::RamCapped(container, _) => {
                 if container.is_empty() {
                     None
                 } else {
                     Some(container.clone())
                 }
             }
-            Collector::RamAndHeaders(container, _) => {
+            Collector::RamAndHeaders(container, _)
+            | Collector::RamAndHeadersCapped(container, _, _) => {
                 if container.is_empty() {
                     None
                 } else {
@@ -343,35 +864,31 @@ impl ExtendedHandler for Collector {
         }
     }
 
-    /// If Collector::File(`FileInfo`) is set, there will be no response body since the response will be stored into a file.
+    /// If Collector::File(`FileInfo`) is set, there will be no response body since the response will be stored into a file
+    /// but the headers received during the download are generated.
     /// If Collector::Ram(`Vec<u8>`) is set, the response body can be obtain here.
     /// If Collector::RamAndHeaders(`Vec<u8>`, `Vec<u8>`) is set, the response body and the complete headers are generated.
+    /// If Collector::RamAndHeadersCapped(`Vec<u8>`, `Vec<u8>`, `HeaderLimit`) is set, the response body and the headers that fit are generated.
     /// If Collector::FileAndHeaders(`FileInfo`, `Vec<u8>`) is set, there will be no response body since the response will be stored into a file but a complete headers are generated.
     fn get_response_body_and_headers(&self) -> (Option<Vec<u8>>, Option<HeaderMap>) {
         match self {
-            Collector::File(_) => (None, None),
-            Collector::Ram(container) => {
+            Collector::File(info) => {
+                if info.headers.is_empty() {
+                    (None, None)
+                } else {
+                    (None, Some(parse_headers(split_trailers(&info.headers).0)))
+                }
+            }
+            Collector::Ram(container) | Collector::RamCapped(container, _) => {
                 if container.is_empty() {
                     (None, None)
                 } else {
                     (Some(container.clone()), None)
                 }
             }
-            Collector::RamAndHeaders(container, headers) => {
-                let header_str = std::str::from_utf8(headers).unwrap();
-                let mut header_map = HeaderMap::new();
-
-                for line in header_str.lines() {
-                    // Split each line into key-value pairs
-                    if let Some((key, value)) = line.split_once(": ").to_owned() {
-                        if let Ok(header_name) = HeaderName::from_bytes(key.as_bytes()) {
-                            if let Ok(header_value) = HeaderValue::from_str(value) {
-                                // Insert the key-value pair into the HeaderMap
-                                header_map.insert(header_name, header_value);
-                            }
-                        }
-                    }
-                }
+            Collector::RamAndHeaders(container, headers)
+            | Collector::RamAndHeadersCapped(container, headers, _) => {
+                let header_map = parse_headers(split_trailers(headers).0);
                 if container.is_empty() {
                     (None, Some(header_map))
                 } else {
@@ -379,22 +896,754 @@ impl ExtendedHandler for Collector {
                 }
             }
             Collector::FileAndHeaders(_, headers) => {
-                let header_str = std::str::from_utf8(headers).unwrap();
-                let mut header_map = HeaderMap::new();
-
-                for line in header_str.lines() {
-                    // Split each line into key-value pairs
-                    if let Some((key, value)) = line.split_once(": ").to_owned() {
-                        if let Ok(header_name) = HeaderName::from_bytes(key.as_bytes()) {
-                            if let Ok(header_value) = HeaderValue::from_str(value) {
-                                // Insert the key-value pair into the HeaderMap
-                                header_map.insert(header_name, header_value);
-                            }
-                        }
-                    }
-                }
+                let header_map = parse_headers(split_trailers(headers).0);
                 (None, Some(header_map))
             }
         }
     }
+
+    /// If Collector::RamCapped(`Vec<u8>`, `usize`) is set, the maximum size of the response body is returned.
+    fn max_body_size(&self) -> Option<usize> {
+        match self {
+            Collector::RamCapped(_, max_size) => Some(*max_size),
+            _ => None,
+        }
+    }
+
+    /// If Collector::File(`FileInfo`) or Collector::FileAndHeaders(`FileInfo`, `Vec<u8>`) is set, the size of the file
+    /// to be uploaded is returned.
+    fn upload_size(&self) -> Option<u64> {
+        match self {
+            Collector::File(info) | Collector::FileAndHeaders(info, _) => {
+                std::fs::metadata(&info.path).map(|m| m.len()).ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// If Collector::File(`FileInfo`) or Collector::FileAndHeaders(`FileInfo`, `Vec<u8>`) is set, the file is
+    /// created empty if the response had no body, so a successful transfer always leaves the file behind.
+    /// With `fsync_on_complete`, the file is also flushed to the storage device and with `with_atomic_rename`,
+    /// the temporary file is renamed to the path.
+    fn transfer_complete(&mut self) -> std::io::Result<()> {
+        match self {
+            Collector::File(info) | Collector::FileAndHeaders(info, _) => {
+                info.create_if_missing()?;
+                if info.fsync_on_complete {
+                    info.sync_file()?;
+                }
+                info.rename_part()
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// If Collector::File(`FileInfo`) or Collector::FileAndHeaders(`FileInfo`, `Vec<u8>`) is set, the perform
+    /// aborter of the FileInfo is returned.
+    fn perform_aborter(&mut self) -> Option<AbortPerform> {
+        match self {
+            Collector::File(info) | Collector::FileAndHeaders(info, _) => {
+                Some(info.abort.get_or_insert_with(AbortPerform::new).clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// If Collector::File(`FileInfo`) or Collector::FileAndHeaders(`FileInfo`, `Vec<u8>`) is set with
+    /// `store_compressed`, the response body is not decoded.
+    fn store_compressed(&self) -> bool {
+        match self {
+            Collector::File(info) | Collector::FileAndHeaders(info, _) => info.store_compressed,
+            _ => false,
+        }
+    }
+
+    /// If Collector::File(`FileInfo`), Collector::RamAndHeaders(`Vec<u8>`, `Vec<u8>`), Collector::RamAndHeadersCapped(`Vec<u8>`, `Vec<u8>`, `HeaderLimit`)
+    /// or Collector::FileAndHeaders(`FileInfo`, `Vec<u8>`) is set, the status line of the final response is returned.
+    fn status_line(&self) -> Option<String> {
+        match self {
+            Collector::File(info) => last_status_line(&info.headers),
+            Collector::RamAndHeaders(_, headers)
+            | Collector::RamAndHeadersCapped(_, headers, _)
+            | Collector::FileAndHeaders(_, headers) => last_status_line(headers),
+            Collector::Ram(_) | Collector::RamCapped(_, _) => None,
+        }
+    }
+
+    /// If Collector::File(`FileInfo`) or Collector::FileAndHeaders(`FileInfo`, `Vec<u8>`) is set with
+    /// `with_cancellation_token`, the token is returned.
+    fn cancellation_token(&self) -> Option<CancellationToken> {
+        match self {
+            Collector::File(info) | Collector::FileAndHeaders(info, _) => {
+                info.cancellation_token.clone()
+            }
+            _ => None,
+        }
+    }
+
+    /// If Collector::File(`FileInfo`), Collector::RamAndHeaders(`Vec<u8>`, `Vec<u8>`), Collector::RamAndHeadersCapped(`Vec<u8>`, `Vec<u8>`, `HeaderLimit`)
+    /// or Collector::FileAndHeaders(`FileInfo`, `Vec<u8>`) is set, the trailers of the final response are returned.
+    fn trailers(&self) -> Option<HeaderMap> {
+        match self {
+            Collector::File(info) => parse_trailers(&info.headers),
+            Collector::RamAndHeaders(_, headers)
+            | Collector::RamAndHeadersCapped(_, headers, _)
+            | Collector::FileAndHeaders(_, headers) => parse_trailers(headers),
+            Collector::Ram(_) | Collector::RamCapped(_, _) => None,
+        }
+    }
+
+    /// The body and the headers of the redirect response are emptied, a file is truncated back to
+    /// the length it had before the redirect response was written to it.
+    fn discard_response(&mut self) -> std::io::Result<()> {
+        match self {
+            Collector::File(info) => {
+                info.headers.clear();
+                info.discard_written()
+            }
+            Collector::FileAndHeaders(info, headers) => {
+                headers.clear();
+                info.discard_written()
+            }
+            _ => {
+                self.clear_response();
+                Ok(())
+            }
+        }
+    }
+
+    /// If Collector::File(`FileInfo`) or Collector::FileAndHeaders(`FileInfo`, `Vec<u8>`) is set with
+    /// `delete_on_error` or `with_atomic_rename`, the path of the file that is written is returned.
+    fn remove_on_error(&self) -> Option<PathBuf> {
+        match self {
+            Collector::File(info) | Collector::FileAndHeaders(info, _)
+                if info.delete_on_error || info.atomic_rename =>
+            {
+                Some(info.write_path())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The number of received chunks that can be queued for the writer thread of the BackgroundFileCollector.
+const BACKGROUND_FILE_QUEUE: usize = 64;
+
+/// BackgroundFileCollector is used to download a file like Collector::File(FileInfo) but the disk I/O
+/// is done in a dedicated thread.
+///
+/// The received chunks are handed over to the writer thread, so a slow storage does not stall the
+/// actor that performs the other concurrent transfers. If the writer thread falls behind by more than
+/// a fixed number of chunks, the transfer waits for it to catch up.
+#[derive(Debug)]
+pub struct BackgroundFileCollector {
+    /// File path to download.
+    pub path: PathBuf,
+    sender: Option<SyncSender<Vec<u8>>>,
+    writer: Option<JoinHandle<std::io::Result<()>>>,
+}
+
+impl BackgroundFileCollector {
+    /// Sets the destination file path to download and starts the writer thread.
+    pub fn path(path: PathBuf) -> Self {
+        let (sender, receiver) = sync_channel::<Vec<u8>>(BACKGROUND_FILE_QUEUE);
+        let file_path = path.clone();
+
+        let writer = thread::spawn(move || {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(file_path)?;
+
+            for chunk in receiver {
+                file.write_all(&chunk)?;
+            }
+            Ok(())
+        });
+
+        Self {
+            path,
+            sender: Some(sender),
+            writer: Some(writer),
+        }
+    }
+}
+
+impl Handler for BackgroundFileCollector {
+    /// This will hand over the response from the server to the writer thread.
+    fn write(&mut self, data: &[u8]) -> Result<usize, WriteError> {
+        match &self.sender {
+            Some(sender) => match sender.send(data.to_vec()) {
+                Ok(()) => Ok(data.len()),
+                Err(e) => {
+                    // The writer thread has stopped due to an I/O error.
+                    trace!("{}", e);
+                    Ok(0)
+                }
+            },
+            None => Ok(0),
+        }
+    }
+}
+
+impl ExtendedHandler for BackgroundFileCollector {
+    /// This waits for the writer thread to write all the received chunks to the file.
+    fn transfer_complete(&mut self) -> std::io::Result<()> {
+        // Closing the channel lets the writer thread finish.
+        self.sender.take();
+
+        match self.writer.take() {
+            Some(writer) => writer.join().unwrap_or_else(std::panic::resume_unwind),
+            None => Ok(()),
+        }
+    }
+}
+
+/// TeeCollector writes every chunk of the response body to several sinks as it is received, e.g. to save a
+/// download to a file and compute its checksum without reading the file again,
+/// `TeeCollector::new().with_sink(file).with_shared_sink(hasher.clone())`.
+///
+/// The chunk is written to the sinks in the order they were added. If a sink fails, the transfer is aborted
+/// and perform fails, the sinks before it have already received the chunk. The sinks are flushed once the
+/// transfer has completed. The complete headers are collected, so they are part of the response.
+#[derive(Default)]
+pub struct TeeCollector {
+    sinks: Vec<Box<dyn Write + Send>>,
+    headers: Vec<u8>,
+}
+
+impl TeeCollector {
+    /// Creates a TeeCollector without sinks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a sink that is owned by the collector, e.g. a `File`.
+    pub fn with_sink<W: Write + Send + 'static>(mut self, sink: W) -> Self {
+        self.sinks.push(Box::new(sink));
+        self
+    }
+
+    /// Adds a sink that is shared with the caller, e.g. a hasher whose digest is read after the transfer.
+    pub fn with_shared_sink<W: Write + Send + 'static>(mut self, sink: Arc<Mutex<W>>) -> Self {
+        self.sinks.push(Box::new(SharedSink(sink)));
+        self
+    }
+}
+
+impl Debug for TeeCollector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TeeCollector")
+            .field("sinks", &self.sinks.len())
+            .field("headers", &self.headers)
+            .finish()
+    }
+}
+
+/// A sink of a TeeCollector that is shared with the caller.
+struct SharedSink<W>(Arc<Mutex<W>>);
+
+impl<W: Write> Write for SharedSink<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.lock().unwrap().flush()
+    }
+}
+
+impl Handler for TeeCollector {
+    /// This will write the response from the server to every sink.
+    fn write(&mut self, data: &[u8]) -> Result<usize, WriteError> {
+        for sink in &mut self.sinks {
+            if let Err(e) = sink.write_all(data) {
+                trace!("{}", e);
+                return Ok(ABORT_WRITE);
+            }
+        }
+        Ok(data.len())
+    }
+
+    fn header(&mut self, data: &[u8]) -> bool {
+        self.headers.extend_from_slice(data);
+        true
+    }
+}
+
+impl ExtendedHandler for TeeCollector {
+    /// There is no response body since it has been written to the sinks, only the complete headers are generated.
+    fn get_response_body_and_headers(&self) -> (Option<Vec<u8>>, Option<HeaderMap>) {
+        (None, Some(parse_headers(split_trailers(&self.headers).0)))
+    }
+
+    /// This flushes the sinks, so everything that was received has been written to them.
+    fn transfer_complete(&mut self) -> std::io::Result<()> {
+        for sink in &mut self.sinks {
+            sink.flush()?;
+        }
+        Ok(())
+    }
+
+    fn status_line(&self) -> Option<String> {
+        last_status_line(&self.headers)
+    }
+
+    fn trailers(&self) -> Option<HeaderMap> {
+        parse_trailers(&self.headers)
+    }
+
+    /// Only the headers are dropped, the body has already been handed over.
+    fn discard_response(&mut self) -> std::io::Result<()> {
+        self.headers.clear();
+        Ok(())
+    }
+}
+
+/// StreamingCollector hands over the response body in chunks as they are received instead of storing it.
+///
+/// It is used through `AsyncPerform::into_chunk_stream` which yields the chunks as a `futures::Stream`.
+/// The complete headers are collected, so they are part of the response once the transfer has completed.
+/// Performing the request any other way aborts the transfer as soon as the body is received since
+/// nobody consumes the chunks.
+#[derive(Debug, Default)]
+pub struct StreamingCollector {
+    sender: Option<UnboundedSender<Vec<u8>>>,
+    headers: Vec<u8>,
+    max_body_size: Option<usize>,
+    body_size: usize,
+}
+
+impl StreamingCollector {
+    /// Creates a StreamingCollector without a limit on the size of the response body.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum size in bytes of the response body that is streamed, e.g. for an untrusted endpoint.
+    /// The transfer is aborted once the body exceeds it and the stream ends with
+    /// [`Error::BodyTooLarge`](crate::Error::BodyTooLarge), a body of exactly the maximum size is accepted.
+    pub fn with_max_body_size(mut self, max_size: usize) -> Self {
+        self.max_body_size = Some(max_size);
+        self
+    }
+
+    pub(crate) fn set_sender(&mut self, sender: UnboundedSender<Vec<u8>>) {
+        self.sender = Some(sender);
+    }
+}
+
+impl Handler for StreamingCollector {
+    /// This will hand over the response from the server to the stream.
+    fn write(&mut self, data: &[u8]) -> Result<usize, WriteError> {
+        if let Some(max_size) = self.max_body_size {
+            if self.body_size + data.len() > max_size {
+                trace!(
+                    "Response body exceeded the maximum size of {} bytes",
+                    max_size
+                );
+                return Ok(ABORT_WRITE);
+            }
+        }
+        self.body_size += data.len();
+
+        match &self.sender {
+            Some(sender) => match sender.send(data.to_vec()) {
+                Ok(()) => Ok(data.len()),
+                Err(e) => {
+                    // The stream has been dropped.
+                    trace!("{}", e);
+                    Ok(0)
+                }
+            },
+            None => Ok(0),
+        }
+    }
+
+    fn header(&mut self, data: &[u8]) -> bool {
+        self.headers.extend_from_slice(data);
+        true
+    }
+}
+
+impl ExtendedHandler for StreamingCollector {
+    /// There is no response body since it has been handed over to the stream, only the complete headers are generated.
+    fn get_response_body_and_headers(&self) -> (Option<Vec<u8>>, Option<HeaderMap>) {
+        (None, Some(parse_headers(split_trailers(&self.headers).0)))
+    }
+
+    fn max_body_size(&self) -> Option<usize> {
+        self.max_body_size
+    }
+
+    fn status_line(&self) -> Option<String> {
+        last_status_line(&self.headers)
+    }
+
+    fn trailers(&self) -> Option<HeaderMap> {
+        parse_trailers(&self.headers)
+    }
+
+    /// Only the headers are dropped, the body has already been handed over.
+    fn discard_response(&mut self) -> std::io::Result<()> {
+        self.headers.clear();
+        Ok(())
+    }
+}
+
+/// HeaderFunction wraps a collector to call a function for every header line as soon as it is received.
+///
+/// curl's Easy2 takes its callbacks from the collector, so the header function is attached by wrapping
+/// the collector that is passed to `HttpClient::new`. The function is called before the header line is
+/// given to the wrapped collector, so Collector::RamAndHeaders and Collector::FileAndHeaders still capture
+/// the complete headers. Returning false from the function aborts the transfer.
+pub struct HeaderFunction<C, F>
+where
+    C: ExtendedHandler,
+    F: FnMut(&[u8]) -> bool,
+{
+    collector: C,
+    header_function: F,
+}
+
+impl<C, F> HeaderFunction<C, F>
+where
+    C: ExtendedHandler,
+    F: FnMut(&[u8]) -> bool,
+{
+    /// Wraps the collector with the function to be called for every header line.
+    pub fn new(collector: C, header_function: F) -> Self {
+        Self {
+            collector,
+            header_function,
+        }
+    }
+
+    /// Returns the wrapped collector.
+    pub fn collector(&self) -> &C {
+        &self.collector
+    }
+}
+
+impl<C, F> Debug for HeaderFunction<C, F>
+where
+    C: ExtendedHandler + Debug,
+    F: FnMut(&[u8]) -> bool,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HeaderFunction")
+            .field("collector", &self.collector)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C, F> Handler for HeaderFunction<C, F>
+where
+    C: ExtendedHandler,
+    F: FnMut(&[u8]) -> bool,
+{
+    fn write(&mut self, data: &[u8]) -> Result<usize, WriteError> {
+        self.collector.write(data)
+    }
+
+    fn read(&mut self, data: &mut [u8]) -> Result<usize, ReadError> {
+        self.collector.read(data)
+    }
+
+    fn seek(&mut self, whence: SeekFrom) -> SeekResult {
+        self.collector.seek(whence)
+    }
+
+    fn debug(&mut self, kind: InfoType, data: &[u8]) {
+        self.collector.debug(kind, data)
+    }
+
+    fn header(&mut self, data: &[u8]) -> bool {
+        (self.header_function)(data) && self.collector.header(data)
+    }
+
+    fn progress(&mut self, dltotal: f64, dlnow: f64, ultotal: f64, ulnow: f64) -> bool {
+        self.collector.progress(dltotal, dlnow, ultotal, ulnow)
+    }
+
+    fn ssl_ctx(&mut self, cx: *mut std::ffi::c_void) -> Result<(), curl::Error> {
+        self.collector.ssl_ctx(cx)
+    }
+}
+
+forward_extended_handler!(
+    impl[C, F] HeaderFunction<C, F> where [C: ExtendedHandler, F: FnMut(&[u8]) -> bool]
+);
+
+/// AfterFirstByteTimeout wraps a collector to abort the transfer if it has not completed within a time limit
+/// after the first byte of the response arrived, e.g. for a streaming endpoint that starts fast and then hangs.
+///
+/// `timeout` limits the whole request and `low_speed_limit` the throughput, this limits the time the response
+/// takes once it has started. A server that never sends a first byte is covered by `connect_timeout` and `timeout`.
+/// The limit is checked in the progress function, which is enabled when the request is performed, so the transfer
+/// is aborted within about a second after the limit has passed and fails with `Error::Perform`.
+pub struct AfterFirstByteTimeout<C>
+where
+    C: ExtendedHandler,
+{
+    collector: C,
+    timeout: Duration,
+    first_byte: Option<Instant>,
+    timed_out: bool,
+    abort: AbortPerform,
+}
+
+impl<C> AfterFirstByteTimeout<C>
+where
+    C: ExtendedHandler,
+{
+    /// Wraps the collector with the time the response may take after its first byte arrived.
+    pub fn new(collector: C, timeout: Duration) -> Self {
+        Self {
+            collector,
+            timeout,
+            first_byte: None,
+            timed_out: false,
+            abort: AbortPerform::new(),
+        }
+    }
+
+    /// Returns the wrapped collector.
+    pub fn collector(&self) -> &C {
+        &self.collector
+    }
+
+    /// Returns true if the transfer was aborted because it took longer than the time limit.
+    pub fn timed_out(&self) -> bool {
+        self.timed_out
+    }
+
+    fn first_byte_received(&mut self) {
+        self.first_byte.get_or_insert_with(Instant::now);
+    }
+}
+
+impl<C> Debug for AfterFirstByteTimeout<C>
+where
+    C: ExtendedHandler + Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AfterFirstByteTimeout")
+            .field("collector", &self.collector)
+            .field("timeout", &self.timeout)
+            .field("first_byte", &self.first_byte)
+            .field("timed_out", &self.timed_out)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C> Handler for AfterFirstByteTimeout<C>
+where
+    C: ExtendedHandler,
+{
+    fn write(&mut self, data: &[u8]) -> Result<usize, WriteError> {
+        self.first_byte_received();
+        self.collector.write(data)
+    }
+
+    fn read(&mut self, data: &mut [u8]) -> Result<usize, ReadError> {
+        self.collector.read(data)
+    }
+
+    fn seek(&mut self, whence: SeekFrom) -> SeekResult {
+        self.collector.seek(whence)
+    }
+
+    fn debug(&mut self, kind: InfoType, data: &[u8]) {
+        self.collector.debug(kind, data)
+    }
+
+    fn header(&mut self, data: &[u8]) -> bool {
+        self.first_byte_received();
+        self.collector.header(data)
+    }
+
+    fn progress(&mut self, dltotal: f64, dlnow: f64, ultotal: f64, ulnow: f64) -> bool {
+        if *self.abort.lock().unwrap() {
+            return false;
+        }
+        if let Some(first_byte) = self.first_byte {
+            if first_byte.elapsed() > self.timeout {
+                trace!(
+                    "Response did not complete within {:?} after its first byte",
+                    self.timeout
+                );
+                self.timed_out = true;
+                return false;
+            }
+        }
+        self.collector.progress(dltotal, dlnow, ultotal, ulnow)
+    }
+
+    fn ssl_ctx(&mut self, cx: *mut std::ffi::c_void) -> Result<(), curl::Error> {
+        self.collector.ssl_ctx(cx)
+    }
+}
+
+forward_extended_handler!(
+    impl[C] AfterFirstByteTimeout<C> where [C: ExtendedHandler];
+    perform_aborter(wrapper) => Some(wrapper.abort.clone())
+);
+
+/// Cancellable wraps a collector to abort the transfer once a `CancellationToken` is cancelled, e.g. a child
+/// token of a task group that is shut down. For Collector::File(FileInfo) the token can also be set with
+/// `FileInfo::with_cancellation_token`.
+///
+/// The token is checked in the progress function, which is enabled when the request is performed, and before
+/// the received data is written, so the transfer stops soon after the token is cancelled and fails with
+/// `Error::Aborted`.
+pub struct Cancellable<C>
+where
+    C: ExtendedHandler,
+{
+    collector: C,
+    token: CancellationToken,
+    abort: AbortPerform,
+}
+
+impl<C> Cancellable<C>
+where
+    C: ExtendedHandler,
+{
+    /// Wraps the collector with the token that aborts the transfer.
+    pub fn new(collector: C, token: CancellationToken) -> Self {
+        Self {
+            collector,
+            token,
+            abort: AbortPerform::new(),
+        }
+    }
+
+    /// Returns the wrapped collector.
+    pub fn collector(&self) -> &C {
+        &self.collector
+    }
+}
+
+impl<C> Debug for Cancellable<C>
+where
+    C: ExtendedHandler + Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Cancellable")
+            .field("collector", &self.collector)
+            .field("cancelled", &self.token.is_cancelled())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C> Handler for Cancellable<C>
+where
+    C: ExtendedHandler,
+{
+    fn write(&mut self, data: &[u8]) -> Result<usize, WriteError> {
+        if self.token.is_cancelled() {
+            return Ok(ABORT_WRITE);
+        }
+        self.collector.write(data)
+    }
+
+    fn read(&mut self, data: &mut [u8]) -> Result<usize, ReadError> {
+        self.collector.read(data)
+    }
+
+    fn seek(&mut self, whence: SeekFrom) -> SeekResult {
+        self.collector.seek(whence)
+    }
+
+    fn debug(&mut self, kind: InfoType, data: &[u8]) {
+        self.collector.debug(kind, data)
+    }
+
+    fn header(&mut self, data: &[u8]) -> bool {
+        self.collector.header(data)
+    }
+
+    fn progress(&mut self, dltotal: f64, dlnow: f64, ultotal: f64, ulnow: f64) -> bool {
+        if self.token.is_cancelled() {
+            trace!("Transfer was cancelled");
+            return false;
+        }
+        if *self.abort.lock().unwrap() {
+            return false;
+        }
+        self.collector.progress(dltotal, dlnow, ultotal, ulnow)
+    }
+
+    fn ssl_ctx(&mut self, cx: *mut std::ffi::c_void) -> Result<(), curl::Error> {
+        self.collector.ssl_ctx(cx)
+    }
+}
+
+forward_extended_handler!(
+    impl[C] Cancellable<C> where [C: ExtendedHandler];
+    perform_aborter(wrapper) => Some(wrapper.abort.clone());
+    cancellation_token(wrapper) => Some(wrapper.token.clone())
+);
+
+/// Returns the last status line of the raw header lines received by the collector. The headers
+/// of interim responses like `100 Continue` and of followed redirects come before the final one.
+fn last_status_line(headers: &[u8]) -> Option<String> {
+    String::from_utf8_lossy(headers)
+        .lines()
+        .rev()
+        .find(|line| line.starts_with("HTTP/"))
+        .map(|line| line.trim_end().to_string())
+}
+
+/// Splits the raw header lines received by the collector into the headers and the trailers of the final response.
+/// libcurl passes the trailers of a chunked or an HTTP/2 response to the header function once the body has been
+/// received, so they follow the empty line that ends the headers of the final response.
+fn split_trailers(headers: &[u8]) -> (&[u8], &[u8]) {
+    let mut start = 0;
+    let mut final_response = false;
+    let mut end_of_headers = None;
+    for line in headers.split_inclusive(|&byte| byte == b'\n') {
+        if line.starts_with(b"HTTP/") {
+            // The headers of interim responses and of followed redirects come before the final one.
+            final_response = true;
+            end_of_headers = None;
+        } else if final_response && end_of_headers.is_none() && (line == b"\r\n" || line == b"\n") {
+            end_of_headers = Some(start + line.len());
+        }
+        start += line.len();
+    }
+
+    match end_of_headers {
+        Some(end) => headers.split_at(end),
+        None => (headers, &[]),
+    }
+}
+
+/// Parses the trailers of the raw header lines, None if the final response had no trailers.
+fn parse_trailers(headers: &[u8]) -> Option<HeaderMap> {
+    Some(parse_headers(split_trailers(headers).1)).filter(|trailers| !trailers.is_empty())
+}
+
+/// Parses the raw header lines received by the collector into a HeaderMap.
+fn parse_headers(headers: &[u8]) -> HeaderMap {
+    let mut header_map = HeaderMap::new();
+
+    // The headers are parsed from the bytes since a value may not be UTF-8, e.g. a Latin-1 file name.
+    for line in headers.split(|b| *b == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        // Split each line into key-value pairs
+        let position = match line.windows(2).position(|window| window == b": ") {
+            Some(position) => position,
+            None => continue,
+        };
+        let (key, value) = (&line[..position], &line[position + 2..]);
+        if let Ok(header_name) = HeaderName::from_bytes(key) {
+            if let Ok(header_value) = HeaderValue::from_bytes(value) {
+                // Append the key-value pair, a header like Link may be sent on several lines.
+                header_map.append(header_name, header_value);
+            }
+        }
+    }
+    header_map
 }
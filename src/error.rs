@@ -1,5 +1,7 @@
 use std::fmt::Debug;
 
+use http::HeaderMap;
+
 use crate::ExtendedHandler;
 
 /// Error type returned by failed curl HTTP requests.
@@ -10,8 +12,32 @@ where
 {
     Curl(curl::Error),
     Http(String),
+    /// The transfer failed. If a blocking transfer failed in a system call, e.g. connecting to a
+    /// server that refused the connection, the extra description of the curl error ends with the
+    /// error of the operating system (`CURLINFO_OS_ERRNO`), e.g. `Connection refused (os error 111)`.
+    ///
+    /// The handle of a failed asynchronous transfer is not given back by the actor, so its curl
+    /// error does not have the error of the operating system.
     Perform(async_curl::error::Error<C>),
+    /// The response body exceeded the maximum size in bytes accepted by the collector.
+    BodyTooLarge(usize),
+    /// The Content-Type of the response does not start with the one expected with `expect_content_type`.
+    UnexpectedContentType {
+        expected: String,
+        actual: Option<String>,
+    },
     Other(String),
+    /// A blocking transfer with `headers_on_timeout` timed out. This has the error of curl and the
+    /// status line and the headers of the response received before the timeout, which are `None` if
+    /// the timeout fired before them or if the collector does not keep the headers.
+    Timeout {
+        error: curl::Error,
+        status_line: Option<String>,
+        headers: Option<HeaderMap>,
+    },
+    /// The transfer was aborted because the `CancellationToken` of the collector was cancelled,
+    /// see `FileInfo::with_cancellation_token` and `Cancellable`.
+    Aborted,
 }
 
 impl<C> std::fmt::Display for Error<C>
@@ -23,7 +49,31 @@ where
             Error::Curl(err) => write!(f, "{}", err),
             Error::Http(err) => write!(f, "{}", err),
             Error::Perform(err) => write!(f, "{}", err),
+            Error::BodyTooLarge(max_size) => write!(
+                f,
+                "response body exceeded the maximum size of {} bytes",
+                max_size
+            ),
+            Error::UnexpectedContentType { expected, actual } => match actual {
+                Some(actual) => write!(
+                    f,
+                    "expected content type {} but the response has {}",
+                    expected, actual
+                ),
+                None => write!(
+                    f,
+                    "expected content type {} but the response has none",
+                    expected
+                ),
+            },
             Error::Other(err) => write!(f, "{}", err),
+            Error::Timeout {
+                error,
+                status_line: Some(status_line),
+                ..
+            } => write!(f, "{} after receiving {}", error, status_line),
+            Error::Timeout { error, .. } => write!(f, "{}", error),
+            Error::Aborted => write!(f, "the transfer was cancelled"),
         }
     }
 }
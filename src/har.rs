@@ -0,0 +1,293 @@
+use std::{
+    fmt::Debug,
+    io::SeekFrom,
+    time::{Duration, SystemTime},
+};
+
+#[cfg(feature = "json")]
+use base64::{engine::general_purpose::STANDARD, Engine};
+use curl::easy::{Handler, InfoType, ReadError, SeekResult, WriteError};
+#[cfg(feature = "json")]
+use serde::Serialize;
+#[cfg(feature = "json")]
+use serde_json::{json, Value};
+#[cfg(feature = "json")]
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use crate::{collector::forward_extended_handler, ExtendedHandler};
+
+/// HarRecord is the record of a request and its response made by the Recorder collector.
+///
+/// It is added to the extensions of the response, and can be exported in the HAR 1.2 format
+/// with `to_har_json` or serialized as it is with serde, both need the `json` feature.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub struct HarRecord {
+    /// The time at which the request was started.
+    pub started: Option<SystemTime>,
+    /// The method of the request.
+    pub method: String,
+    /// The effective url of the request, this is the last url if redirects were followed.
+    pub url: String,
+    /// The HTTP version of the request line, e.g. `HTTP/1.1`.
+    pub http_version: String,
+    /// The header lines exactly as they were sent by curl.
+    pub request_headers: Vec<(String, String)>,
+    /// The body that was sent with the request.
+    pub request_body: Vec<u8>,
+    /// The status code of the response.
+    pub status: u16,
+    /// The reason phrase of the status line of the response.
+    pub status_text: String,
+    /// The header lines of the response.
+    pub response_headers: Vec<(String, String)>,
+    /// The body of the response that was accepted by the collector.
+    pub response_body: Vec<u8>,
+    /// The time spent in each phase of the transfer.
+    pub timings: HarTimings,
+}
+
+/// HarTimings is the time spent in each phase of the transfer.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub struct HarTimings {
+    /// The time spent resolving the name of the host.
+    pub dns: Duration,
+    /// The time spent to connect to the host, without the TLS handshake.
+    pub connect: Duration,
+    /// The time spent in the TLS handshake, `None` if there was no handshake.
+    pub ssl: Option<Duration>,
+    /// The time spent sending the request.
+    pub send: Duration,
+    /// The time spent waiting for the first byte of the response.
+    pub wait: Duration,
+    /// The time spent receiving the response.
+    pub receive: Duration,
+    /// The total time of the transfer.
+    pub total: Duration,
+}
+
+#[cfg(feature = "json")]
+impl HarRecord {
+    /// Exports the record as a HAR 1.2 log with a single entry.
+    pub fn to_har_json(&self) -> String {
+        let started = OffsetDateTime::from(self.started.unwrap_or_else(SystemTime::now));
+        let redirect_url = header_value(&self.response_headers, "location");
+        let request_content_type = header_value(&self.request_headers, "content-type");
+        let response_content_type = header_value(&self.response_headers, "content-type");
+
+        let mut request = json!({
+            "method": self.method,
+            "url": self.url,
+            "httpVersion": self.http_version,
+            "cookies": [],
+            "headers": har_headers(&self.request_headers),
+            "queryString": [],
+            "headersSize": -1,
+            "bodySize": self.request_body.len(),
+        });
+        if !self.request_body.is_empty() {
+            request["postData"] = json!({
+                "mimeType": request_content_type.unwrap_or_default(),
+                "text": String::from_utf8_lossy(&self.request_body),
+            });
+        }
+
+        let mut content = json!({
+            "size": self.response_body.len(),
+            "mimeType": response_content_type.unwrap_or_default(),
+        });
+        match std::str::from_utf8(&self.response_body) {
+            Ok(text) => content["text"] = json!(text),
+            Err(_) => {
+                content["text"] = json!(STANDARD.encode(&self.response_body));
+                content["encoding"] = json!("base64");
+            }
+        }
+
+        let timings = &self.timings;
+        json!({
+            "log": {
+                "version": "1.2",
+                "creator": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+                "entries": [{
+                    "startedDateTime": started.format(&Rfc3339).unwrap_or_default(),
+                    "time": millis(timings.total),
+                    "request": request,
+                    "response": {
+                        "status": self.status,
+                        "statusText": self.status_text,
+                        "httpVersion": self.http_version,
+                        "cookies": [],
+                        "headers": har_headers(&self.response_headers),
+                        "content": content,
+                        "redirectURL": redirect_url.unwrap_or_default(),
+                        "headersSize": -1,
+                        "bodySize": self.response_body.len(),
+                    },
+                    "cache": {},
+                    "timings": {
+                        "blocked": -1,
+                        "dns": millis(timings.dns),
+                        "connect": millis(timings.connect + timings.ssl.unwrap_or_default()),
+                        "ssl": timings.ssl.map(millis).unwrap_or(-1.0),
+                        "send": millis(timings.send),
+                        "wait": millis(timings.wait),
+                        "receive": millis(timings.receive),
+                    },
+                }],
+            }
+        })
+        .to_string()
+    }
+}
+
+/// Recorder wraps a collector to record the request and the response into a HarRecord.
+///
+/// The request line and the request headers are captured from curl's debug function, so they are
+/// exactly the ones that were sent. curl only calls the debug function when `CURLOPT_VERBOSE` is
+/// enabled, `HttpClient::recorded` creates the client with it already enabled. The verbose output
+/// is not passed on to the wrapped collector.
+///
+/// If redirects are followed, the record contains the last request and its response.
+#[derive(Debug)]
+pub struct Recorder<C>
+where
+    C: ExtendedHandler + Debug,
+{
+    collector: C,
+    record: HarRecord,
+}
+
+impl<C> Recorder<C>
+where
+    C: ExtendedHandler + Debug,
+{
+    /// Wraps the collector to record the request and the response.
+    pub fn new(collector: C) -> Self {
+        Self {
+            collector,
+            record: HarRecord::default(),
+        }
+    }
+
+    /// Returns the wrapped collector.
+    pub fn collector(&self) -> &C {
+        &self.collector
+    }
+
+    /// Returns what has been recorded so far.
+    pub fn record(&self) -> &HarRecord {
+        &self.record
+    }
+
+    fn record_request_head(&mut self, data: &[u8]) {
+        let head = String::from_utf8_lossy(data);
+        let mut lines = head.lines();
+
+        // A new request is sent, e.g. after a redirect, so the previous one is discarded.
+        let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+        self.record.method = request_line.next().unwrap_or_default().to_string();
+        self.record.http_version = request_line.nth(1).unwrap_or_default().to_string();
+        self.record.request_headers = lines.filter_map(split_header).collect();
+        self.record.request_body.clear();
+    }
+
+    fn record_response_header(&mut self, data: &[u8]) {
+        let line = String::from_utf8_lossy(data);
+        let line = line.trim_end();
+
+        if line.starts_with("HTTP/") {
+            // The headers of an interim or a redirect response are replaced by the next response.
+            let mut status_line = line.splitn(3, ' ');
+            self.record.http_version = status_line.next().unwrap_or_default().to_string();
+            self.record.status = status_line
+                .next()
+                .and_then(|status| status.parse().ok())
+                .unwrap_or_default();
+            self.record.status_text = status_line.next().unwrap_or_default().to_string();
+            self.record.response_headers.clear();
+            self.record.response_body.clear();
+        } else if let Some(header) = split_header(line) {
+            self.record.response_headers.push(header);
+        }
+    }
+}
+
+impl<C> Handler for Recorder<C>
+where
+    C: ExtendedHandler + Debug,
+{
+    fn write(&mut self, data: &[u8]) -> Result<usize, WriteError> {
+        let written = self.collector.write(data)?;
+        self.record
+            .response_body
+            .extend_from_slice(&data[..written.min(data.len())]);
+        Ok(written)
+    }
+
+    fn read(&mut self, data: &mut [u8]) -> Result<usize, ReadError> {
+        self.collector.read(data)
+    }
+
+    fn seek(&mut self, whence: SeekFrom) -> SeekResult {
+        self.collector.seek(whence)
+    }
+
+    fn debug(&mut self, kind: InfoType, data: &[u8]) {
+        match kind {
+            InfoType::HeaderOut => self.record_request_head(data),
+            InfoType::DataOut => self.record.request_body.extend_from_slice(data),
+            _ => {}
+        }
+    }
+
+    fn header(&mut self, data: &[u8]) -> bool {
+        self.record_response_header(data);
+        self.collector.header(data)
+    }
+
+    fn progress(&mut self, dltotal: f64, dlnow: f64, ultotal: f64, ulnow: f64) -> bool {
+        self.collector.progress(dltotal, dlnow, ultotal, ulnow)
+    }
+
+    fn ssl_ctx(&mut self, cx: *mut std::ffi::c_void) -> Result<(), curl::Error> {
+        self.collector.ssl_ctx(cx)
+    }
+}
+
+// The url, the status and the timings of the record are filled in from the transfer when the
+// response is built.
+forward_extended_handler!(
+    impl[C] Recorder<C> where [C: ExtendedHandler + Debug];
+    har_record(recorder) => Some(recorder.record.clone())
+);
+
+fn split_header(line: &str) -> Option<(String, String)> {
+    let (name, value) = line.split_once(':')?;
+    Some((name.trim().to_string(), value.trim().to_string()))
+}
+
+#[cfg(feature = "json")]
+fn header_value<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(header, _)| header.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+#[cfg(feature = "json")]
+fn har_headers(headers: &[(String, String)]) -> Value {
+    headers
+        .iter()
+        .map(|(name, value)| json!({ "name": name, "value": value }))
+        .collect()
+}
+
+#[cfg(feature = "json")]
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
@@ -176,7 +176,6 @@
 //!
 //! ## Resume Downloading a File
 //! ```rust,no_run
-//! use std::fs;
 //! use std::path::PathBuf;
 //!
 //! use async_curl::CurlActor;
@@ -190,7 +189,6 @@
 //!     let save_to = PathBuf::from("<FILE PATH TO SAVE>");
 //!     let collector = Collector::File(FileInfo::path(save_to.clone()));
 //!
-//!     let partial_download_file_size = fs::metadata(save_to.as_path()).unwrap().len() as usize;
 //!     let request = Request::builder()
 //!         .uri("<SOURCE URL>")
 //!         .method(Method::GET)
@@ -198,7 +196,7 @@
 //!         .unwrap();
 //!
 //!     let response = HttpClient::new(collector)
-//!         .resume_from(BytesOffset::from(partial_download_file_size)).unwrap()
+//!         .resume_from(FileInfo::current_offset(&save_to).unwrap()).unwrap()
 //!         .request(request).unwrap()
 //!         .nonblocking(actor)
 //!         .perform()
@@ -398,7 +396,17 @@
 //!
 pub mod collector;
 pub mod error;
+pub mod har;
 pub mod http_client;
+pub mod mime;
+#[cfg(feature = "json")]
+pub mod ndjson;
+pub mod parallel;
+pub mod request;
+pub mod response;
+pub mod resume;
+pub mod session;
+pub mod sse;
 
 pub mod dep {
     pub use curl;
@@ -409,4 +417,14 @@ mod test;
 
 pub use collector::*;
 pub use error::*;
+pub use har::*;
 pub use http_client::*;
+pub use mime::*;
+#[cfg(feature = "json")]
+pub use ndjson::*;
+pub use parallel::*;
+pub use request::*;
+pub use response::*;
+pub use resume::*;
+pub use session::*;
+pub use sse::*;
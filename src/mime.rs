@@ -0,0 +1,183 @@
+use std::{
+    ffi::{c_void, CString},
+    os::raw::c_char,
+    path::{Path, PathBuf},
+};
+
+/// A `multipart/form-data` body built with libcurl's MIME API, which replaces the deprecated
+/// form API of `CURLOPT_HTTPPOST`.
+///
+/// The parts are sent in the order they were added, e.g.
+/// `MimeForm::new().text("title", "report").file("attachment", "report.pdf")`.
+/// The form is posted with `HttpClient::mime_form`.
+#[derive(Clone, Debug, Default)]
+pub struct MimeForm {
+    parts: Vec<MimePart>,
+}
+
+#[derive(Clone, Debug)]
+enum MimePart {
+    Text {
+        name: String,
+        value: String,
+    },
+    File {
+        name: String,
+        path: PathBuf,
+    },
+    Data {
+        name: String,
+        bytes: Vec<u8>,
+        filename: String,
+        content_type: String,
+    },
+}
+
+impl MimeForm {
+    /// Creates an empty form.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a text field.
+    pub fn text(mut self, name: &str, value: &str) -> Self {
+        self.parts.push(MimePart::Text {
+            name: name.to_string(),
+            value: value.to_string(),
+        });
+        self
+    }
+
+    /// Adds the contents of the file as a part, its file name is the last component of the path.
+    ///
+    /// The file is not loaded into memory, libcurl reads it from disk while it is sent,
+    /// so large files can be posted as well.
+    pub fn file<P: AsRef<Path>>(mut self, name: &str, path: P) -> Self {
+        self.parts.push(MimePart::File {
+            name: name.to_string(),
+            path: path.as_ref().to_path_buf(),
+        });
+        self
+    }
+
+    /// Adds the bytes as a file part with the given file name and content type,
+    /// e.g. `("avatar", png, "avatar.png", "image/png")`.
+    pub fn data(mut self, name: &str, bytes: Vec<u8>, filename: &str, content_type: &str) -> Self {
+        self.parts.push(MimePart::Data {
+            name: name.to_string(),
+            bytes,
+            filename: filename.to_string(),
+            content_type: content_type.to_string(),
+        });
+        self
+    }
+
+    /// Checks that the files of the form can be read before the transfer is started.
+    pub(crate) fn check_files(&self) -> Result<(), String> {
+        for part in &self.parts {
+            if let MimePart::File { path, .. } = part {
+                std::fs::File::open(path)
+                    .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Builds the `curl_mime` of the form for the handle.
+    pub(crate) fn build(&self, easy: *mut curl_sys::CURL) -> Result<Mime, curl::Error> {
+        // libcurl copies the names, the values and the bytes into the mime structure.
+        let mime = Mime(unsafe { curl_mime_init(easy) });
+        if mime.0.is_null() {
+            return Err(curl::Error::new(curl_sys::CURLE_OUT_OF_MEMORY));
+        }
+
+        for part in &self.parts {
+            let raw = unsafe { curl_mime_addpart(mime.0) };
+            if raw.is_null() {
+                return Err(curl::Error::new(curl_sys::CURLE_OUT_OF_MEMORY));
+            }
+
+            match part {
+                MimePart::Text { name, value } => {
+                    set_str(raw, curl_mime_name, name)?;
+                    check(unsafe {
+                        curl_mime_data(raw, value.as_ptr() as *const c_char, value.len())
+                    })?;
+                }
+                MimePart::File { name, path } => {
+                    set_str(raw, curl_mime_name, name)?;
+                    let path = path
+                        .to_str()
+                        .ok_or_else(|| curl::Error::new(curl_sys::CURLE_BAD_FUNCTION_ARGUMENT))?;
+                    // This also sets the file name of the part to the last component of the path.
+                    set_str(raw, curl_mime_filedata, path)?;
+                }
+                MimePart::Data {
+                    name,
+                    bytes,
+                    filename,
+                    content_type,
+                } => {
+                    set_str(raw, curl_mime_name, name)?;
+                    check(unsafe {
+                        curl_mime_data(raw, bytes.as_ptr() as *const c_char, bytes.len())
+                    })?;
+                    set_str(raw, curl_mime_filename, filename)?;
+                    set_str(raw, curl_mime_type, content_type)?;
+                }
+            }
+        }
+        Ok(mime)
+    }
+}
+
+/// The `curl_mime` set as `CURLOPT_MIMEPOST`, which is freed once libcurl no longer uses it.
+#[derive(Debug)]
+pub(crate) struct Mime(*mut c_void);
+
+// The mime structure is not modified once it has been built.
+unsafe impl Send for Mime {}
+unsafe impl Sync for Mime {}
+
+impl Mime {
+    pub(crate) fn as_ptr(&self) -> *const c_void {
+        self.0
+    }
+}
+
+impl Drop for Mime {
+    fn drop(&mut self) {
+        // The mime structure and its parts are owned by this struct, a null mime is ignored.
+        unsafe { curl_mime_free(self.0) }
+    }
+}
+
+/// Sets a string property of the part with one of the `curl_mime_*` functions.
+fn set_str(
+    part: *mut c_void,
+    function: unsafe extern "C" fn(*mut c_void, *const c_char) -> curl_sys::CURLcode,
+    value: &str,
+) -> Result<(), curl::Error> {
+    let value = CString::new(value)?;
+    check(unsafe { function(part, value.as_ptr()) })
+}
+
+fn check(rc: curl_sys::CURLcode) -> Result<(), curl::Error> {
+    if rc == curl_sys::CURLE_OK {
+        Ok(())
+    } else {
+        Err(curl::Error::new(rc))
+    }
+}
+
+// The MIME API of libcurl, which the curl crate does not provide.
+extern "C" {
+    fn curl_mime_init(easy: *mut curl_sys::CURL) -> *mut c_void;
+    fn curl_mime_free(mime: *mut c_void);
+    fn curl_mime_addpart(mime: *mut c_void) -> *mut c_void;
+    fn curl_mime_name(part: *mut c_void, name: *const c_char) -> curl_sys::CURLcode;
+    fn curl_mime_filename(part: *mut c_void, filename: *const c_char) -> curl_sys::CURLcode;
+    fn curl_mime_type(part: *mut c_void, mimetype: *const c_char) -> curl_sys::CURLcode;
+    fn curl_mime_data(part: *mut c_void, data: *const c_char, size: usize) -> curl_sys::CURLcode;
+    fn curl_mime_filedata(part: *mut c_void, filename: *const c_char) -> curl_sys::CURLcode;
+}
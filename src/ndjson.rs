@@ -0,0 +1,127 @@
+use std::{
+    collections::VecDeque,
+    marker::PhantomData,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use futures::Stream;
+use http::Response;
+use serde::de::DeserializeOwned;
+
+use crate::{ChunkStream, Error, StreamingCollector};
+
+/// NdJsonDecoder parses the chunks of a newline-delimited JSON (`application/x-ndjson`) stream,
+/// every line is deserialized into a separate `T`.
+///
+/// The chunks can be split anywhere, an incomplete line is buffered until the rest of it is received.
+/// Blank lines are skipped and a line may end with `\r\n` as well.
+#[derive(Debug)]
+pub struct NdJsonDecoder<T> {
+    buffer: Vec<u8>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for NdJsonDecoder<T> {
+    fn default() -> Self {
+        Self {
+            buffer: Vec::new(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: DeserializeOwned> NdJsonDecoder<T> {
+    /// Creates a NdJsonDecoder with nothing buffered.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses the next chunk of the stream and returns the values of the lines completed by it.
+    pub fn decode(&mut self, chunk: &[u8]) -> Vec<serde_json::Result<T>> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut values = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            values.extend(parse_line(&line));
+        }
+        values
+    }
+
+    /// Parses the last line once the stream has ended, since it does not have to end with a newline.
+    pub fn finish(&mut self) -> Option<serde_json::Result<T>> {
+        let line = std::mem::take(&mut self.buffer);
+        parse_line(&line)
+    }
+}
+
+fn parse_line<T: DeserializeOwned>(line: &[u8]) -> Option<serde_json::Result<T>> {
+    if line.iter().all(u8::is_ascii_whitespace) {
+        return None;
+    }
+    // serde_json ignores the whitespace around the value, including the line ending.
+    Some(serde_json::from_slice(line))
+}
+
+/// NdJsonStream yields the values of a newline-delimited JSON response received through a ChunkStream.
+///
+/// A line that cannot be deserialized into `T` is yielded as `Error::Other` and the stream continues
+/// with the next line.
+pub struct NdJsonStream<T> {
+    chunks: ChunkStream,
+    decoder: NdJsonDecoder<T>,
+    values: VecDeque<serde_json::Result<T>>,
+    ended: bool,
+}
+
+impl<T: DeserializeOwned> NdJsonStream<T> {
+    /// Creates the NdJsonStream from the ChunkStream returned by `AsyncPerform::into_chunk_stream`.
+    pub fn new(chunks: ChunkStream) -> Self {
+        Self {
+            chunks,
+            decoder: NdJsonDecoder::new(),
+            values: VecDeque::new(),
+            ended: false,
+        }
+    }
+
+    /// Returns the status and the headers of the response once the stream has ended
+    /// and the transfer was successful.
+    pub fn response(&self) -> Option<&Response<()>> {
+        self.chunks.response()
+    }
+}
+
+// The values are only moved out of the queue, they are never pinned.
+impl<T> Unpin for NdJsonStream<T> {}
+
+impl<T: DeserializeOwned> Stream for NdJsonStream<T> {
+    type Item = Result<T, Error<StreamingCollector>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(value) = self.values.pop_front() {
+                return Poll::Ready(Some(
+                    value.map_err(|e| Error::Other(format!("invalid JSON line: {}", e))),
+                ));
+            }
+            if self.ended {
+                return Poll::Ready(None);
+            }
+
+            match ready!(Pin::new(&mut self.chunks).poll_next(cx)) {
+                Some(Ok(chunk)) => {
+                    let values = self.decoder.decode(&chunk);
+                    self.values.extend(values);
+                }
+                Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+                None => {
+                    self.ended = true;
+                    let last = self.decoder.finish();
+                    self.values.extend(last);
+                }
+            }
+        }
+    }
+}
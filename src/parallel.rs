@@ -0,0 +1,151 @@
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+use async_curl::Actor;
+use futures::future;
+use http::{Method, Request, StatusCode};
+use log::trace;
+
+use crate::{Collector, Error, FileInfo, HttpClient, ResponseExt};
+
+/// Downloads the resource at the given url into a file by splitting it into ranges
+/// that are requested concurrently through the actor.
+///
+/// Each range is written directly to its offset in the destination file, so the file is
+/// complete once this returns successfully. A first request for a single byte is used to
+/// learn the total size of the resource. If the server does not support range requests,
+/// the resource is downloaded in a single stream instead.
+///
+/// A response that is not successful, e.g. `404 Not Found`, fails with `Error::Other` and the
+/// file is removed, so an error page is never saved as the resource.
+pub async fn parallel_download<A>(
+    actor: A,
+    url: &str,
+    path: PathBuf,
+    chunks: usize,
+) -> Result<(), Error<Collector>>
+where
+    A: Actor<Collector> + Clone,
+{
+    let result = download_ranges(actor, url, path.clone(), chunks).await;
+    if result.is_err() {
+        // The file may hold a part of the resource or the body of an error response.
+        let _ = fs::remove_file(&path);
+    }
+    result
+}
+
+async fn download_ranges<A>(
+    actor: A,
+    url: &str,
+    path: PathBuf,
+    chunks: usize,
+) -> Result<(), Error<Collector>>
+where
+    A: Actor<Collector> + Clone,
+{
+    create_file(&path)?;
+
+    let probe = HttpClient::new(Collector::FileAndHeaders(
+        FileInfo::path(path.clone()),
+        Vec::new(),
+    ))
+    .range("0-0")?
+    .request(get_request(url)?)?
+    .nonblocking(actor.clone())
+    .perform()
+    .await?;
+
+    check_status(probe.status())?;
+    if probe.status() != StatusCode::PARTIAL_CONTENT {
+        // The server ignored the range and sent the whole resource.
+        return Ok(());
+    }
+
+    let total_size = match probe.content_range().and_then(|range| range.total()) {
+        Some(total_size) => total_size,
+        None => return single_download(actor, url, path).await,
+    };
+
+    let file = create_file(&path)?;
+    file.set_len(total_size).map_err(|e| {
+        trace!("{:?}", e);
+        Error::Other(e.to_string())
+    })?;
+
+    let chunks = chunks.max(1) as u64;
+    let chunk_size = total_size / chunks + u64::from(total_size % chunks != 0);
+
+    let downloads = (0..chunks)
+        .map(|n| n * chunk_size)
+        .take_while(|start| *start < total_size)
+        .map(|start| {
+            let end = (start + chunk_size).min(total_size) - 1;
+            let collector = Collector::File(FileInfo::path(path.clone()).at_offset(start));
+            let actor = actor.clone();
+
+            async move {
+                HttpClient::new(collector)
+                    .range(format!("{}-{}", start, end).as_str())?
+                    .request(get_request(url)?)?
+                    .nonblocking(actor)
+                    .perform()
+                    .await
+            }
+        });
+
+    let responses = future::try_join_all(downloads).await?;
+
+    for response in &responses {
+        check_status(response.status())?;
+    }
+    if responses
+        .iter()
+        .any(|response| response.status() != StatusCode::PARTIAL_CONTENT)
+    {
+        return single_download(actor, url, path).await;
+    }
+    Ok(())
+}
+
+async fn single_download<A>(actor: A, url: &str, path: PathBuf) -> Result<(), Error<Collector>>
+where
+    A: Actor<Collector>,
+{
+    create_file(&path)?;
+
+    let response = HttpClient::new(Collector::File(FileInfo::path(path)))
+        .request(get_request(url)?)?
+        .nonblocking(actor)
+        .perform()
+        .await?;
+    check_status(response.status())
+}
+
+fn check_status(status: StatusCode) -> Result<(), Error<Collector>> {
+    if status.is_success() {
+        Ok(())
+    } else {
+        Err(Error::Other(format!(
+            "the server responded with {} instead of the resource",
+            status
+        )))
+    }
+}
+
+fn get_request(url: &str) -> Result<Request<Option<Vec<u8>>>, Error<Collector>> {
+    Request::builder()
+        .uri(url)
+        .method(Method::GET)
+        .body(None)
+        .map_err(|e| Error::Http(e.to_string()))
+}
+
+fn create_file(path: &Path) -> Result<File, Error<Collector>> {
+    File::create(path).map_err(|e| {
+        trace!("{:?}", e);
+        Error::Other(e.to_string())
+    })
+}
@@ -0,0 +1,79 @@
+#[cfg(feature = "json")]
+use std::io;
+
+#[cfg(feature = "json")]
+use flate2::{write::GzEncoder, Compression};
+#[cfg(feature = "json")]
+use http::{
+    header::{CONTENT_ENCODING, CONTENT_TYPE},
+    HeaderValue,
+};
+use http::{request::Builder, Method, Request};
+#[cfg(feature = "json")]
+use serde::Serialize;
+
+/// Starts building a request with the given method and URL, the headers can then be given as
+/// name and value pairs, e.g. `.headers(&[("Accept", "application/json")])`.
+pub fn request_builder(method: Method, url: &str) -> RequestBuilder {
+    RequestBuilder {
+        builder: Request::builder().method(method).uri(url),
+    }
+}
+
+/// A shorter way to build the `http::Request<Option<Vec<u8>>>` that is passed to `HttpClient::request`,
+/// with the headers given as name and value pairs.
+#[derive(Debug)]
+pub struct RequestBuilder {
+    builder: Builder,
+}
+
+impl RequestBuilder {
+    /// Appends a header to the request.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.builder = self.builder.header(name, value);
+        self
+    }
+
+    /// Appends the headers to the request in the given order.
+    pub fn headers(mut self, headers: &[(&str, &str)]) -> Self {
+        for (name, value) in headers {
+            self.builder = self.builder.header(*name, *value);
+        }
+        self
+    }
+
+    /// Finishes the request with the given body.
+    ///
+    /// An invalid URL, header name or header value is returned as `http::Error`.
+    pub fn body(self, body: Option<Vec<u8>>) -> Result<Request<Option<Vec<u8>>>, http::Error> {
+        self.builder.body(body)
+    }
+
+    /// Finishes the request with the value serialized to JSON and compressed with gzip as
+    /// the body, e.g. to post a large payload to an API that accepts compressed requests.
+    ///
+    /// `Content-Type: application/json` and `Content-Encoding: gzip` are set once, replacing
+    /// the same headers if they were given before.
+    ///
+    /// A value that cannot be serialized is returned as `io::ErrorKind::InvalidData`, an invalid
+    /// URL, header name or header value as `io::ErrorKind::InvalidInput`.
+    ///
+    /// This needs the `json` feature.
+    #[cfg(feature = "json")]
+    pub fn json_gzip<T>(mut self, value: &T) -> Result<Request<Option<Vec<u8>>>, io::Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        serde_json::to_writer(&mut encoder, value)?;
+        let body = encoder.finish()?;
+
+        if let Some(headers) = self.builder.headers_mut() {
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+            headers.insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+        }
+        self.builder
+            .body(Some(body))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+    }
+}
@@ -0,0 +1,478 @@
+use std::collections::HashMap;
+
+use http::{
+    header::{CONTENT_DISPOSITION, CONTENT_RANGE, CONTENT_TYPE, ETAG, LINK},
+    HeaderMap, Method, Response, StatusCode,
+};
+
+/// Information about the completed transfer that is not carried by the HTTP response itself.
+///
+/// This is attached to the extensions of the `Response` returned by `perform()` and can be
+/// obtained through [`ResponseExt::transfer_info`].
+#[derive(Clone, Debug, Default)]
+pub struct TransferInfo {
+    /// True if curl skipped the transfer because the condition set with
+    /// `time_condition` (or `if_modified_since`/`if_unmodified_since`) was not met.
+    ///
+    /// This corresponds to `CURLINFO_CONDITION_UNMET`.
+    pub condition_unmet: bool,
+    /// The number of new connections curl had to create for the transfer, 0 means that
+    /// an existing connection was reused.
+    ///
+    /// This corresponds to `CURLINFO_NUM_CONNECTS`.
+    pub num_connects: u32,
+    /// The certificate chain of the TLS connection if it was requested with `certinfo`,
+    /// starting with the certificate of the server.
+    ///
+    /// This corresponds to `CURLINFO_CERTINFO`.
+    pub certificates: Vec<CertInfo>,
+    /// The average download speed of the whole transfer in bytes per second as measured by curl,
+    /// 0 if nothing was downloaded or the transfer took no measurable time.
+    ///
+    /// This corresponds to `CURLINFO_SPEED_DOWNLOAD_T`.
+    pub average_download_speed: u64,
+    /// The average upload speed of the whole transfer in bytes per second as measured by curl,
+    /// 0 if nothing was uploaded or the transfer took no measurable time.
+    ///
+    /// This corresponds to `CURLINFO_SPEED_UPLOAD_T`.
+    pub average_upload_speed: u64,
+    /// The status line of the final response as it was received, e.g. `HTTP/1.1 418 I'm a teapot`,
+    /// to get the reason phrase that `StatusCode` does not keep. HTTP/2 and HTTP/3 have no reason
+    /// phrase, their status line is only the version and the code like `HTTP/2 200`.
+    ///
+    /// The status line is only available if the collector also collects the headers,
+    /// e.g. `Collector::RamAndHeaders` or `Collector::FileAndHeaders`.
+    pub status_line: Option<String>,
+    /// The trailers of the final response, the header fields that are sent after the body of a chunked
+    /// or an HTTP/2 response, e.g. the `grpc-status` of a gRPC call. They are not part of the headers
+    /// of the response.
+    ///
+    /// The trailers are only available if the collector also collects the headers,
+    /// e.g. `Collector::RamAndHeaders` or `Collector::FileAndHeaders`.
+    pub trailers: HeaderMap,
+    /// The method of the last request that was sent, e.g. `PURGE` when it was set with
+    /// `custom_request`, or `GET` when a `303 See Other` was followed.
+    ///
+    /// This corresponds to `CURLINFO_EFFECTIVE_METHOD` and is `None` with libcurl older than 7.72.0.
+    pub method: Option<Method>,
+}
+
+/// The information about a certificate of the chain gathered with `HttpClient::certinfo`.
+///
+/// The fields are provided by the TLS backend of libcurl in the form `Subject`, `Issuer`,
+/// `Start date`, `Expire date`, `Cert` and so on, the names differ slightly between backends.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CertInfo {
+    /// All the fields of the certificate as name and value pairs.
+    pub fields: Vec<(String, String)>,
+}
+
+impl CertInfo {
+    /// Returns the value of the field with the given name, ignoring the case.
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the subject of the certificate, e.g. `CN = example.com`.
+    pub fn subject(&self) -> Option<&str> {
+        self.field("Subject")
+    }
+
+    /// Returns the issuer of the certificate.
+    pub fn issuer(&self) -> Option<&str> {
+        self.field("Issuer")
+    }
+
+    /// Returns the time from which the certificate is valid, e.g. `Jan 1 00:00:00 2024 GMT`.
+    pub fn start_date(&self) -> Option<&str> {
+        self.field("Start date")
+    }
+
+    /// Returns the time at which the certificate expires, e.g. `Jan 1 00:00:00 2025 GMT`.
+    pub fn expire_date(&self) -> Option<&str> {
+        self.field("Expire date")
+    }
+}
+
+impl From<Vec<(String, String)>> for CertInfo {
+    fn from(fields: Vec<(String, String)>) -> Self {
+        Self { fields }
+    }
+}
+
+/// The parsed `Content-Range` header of a `206 Partial Content` or a `416 Range Not Satisfiable` response.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContentRange {
+    /// `bytes 4500-12345/12346`, the positions of the first and the last byte that were sent and the
+    /// complete length of the resource, which is `None` if the server does not know it (`bytes 0-99/*`).
+    Bytes {
+        start: u64,
+        end: u64,
+        total: Option<u64>,
+    },
+    /// `bytes */12346`, the requested range could not be satisfied and this is the complete length.
+    Unsatisfied { total: u64 },
+}
+
+impl ContentRange {
+    /// Returns the complete length of the resource if it is known.
+    pub fn total(&self) -> Option<u64> {
+        match self {
+            ContentRange::Bytes { total, .. } => *total,
+            ContentRange::Unsatisfied { total } => Some(*total),
+        }
+    }
+}
+
+/// A part of the body of a response to a request for several ranges, see [`ResponseBodyExt::byte_ranges`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ByteRange {
+    /// The range of the resource that this part holds, from the `Content-Range` header of the part.
+    pub content_range: Option<ContentRange>,
+    /// The bytes of the range.
+    pub data: Vec<u8>,
+}
+
+/// This is an extension trait for the `Response` returned by `perform()`.
+pub trait ResponseExt {
+    /// Returns the information about the completed transfer, if the response was created by `perform()`.
+    fn transfer_info(&self) -> Option<&TransferInfo>;
+
+    /// Returns true if the server responded with `304 Not Modified` to a conditional request.
+    fn is_not_modified(&self) -> bool;
+
+    /// Returns the file name suggested by the server in the `Content-Disposition` header.
+    ///
+    /// The RFC 5987 form `filename*=UTF-8''na%C3%AFve.txt` is preferred over the plain
+    /// `filename="naive.txt"` form when both are present. The name is reduced to its last
+    /// path component so that it can be joined to a download directory safely.
+    ///
+    /// The response headers are only available if the collector also collects the headers,
+    /// e.g. `Collector::RamAndHeaders` or `Collector::FileAndHeaders`.
+    fn content_disposition_filename(&self) -> Option<String>;
+
+    /// Returns the entity tag of the `ETag` header to be stored and sent back later with
+    /// `HttpClient::if_none_match`.
+    ///
+    /// The value is returned as it was received, e.g. `"abc"` or the weak `W/"abc"`.
+    ///
+    /// The response headers are only available if the collector also collects the headers,
+    /// e.g. `Collector::RamAndHeaders` or `Collector::FileAndHeaders`.
+    fn etag(&self) -> Option<&str>;
+
+    /// Returns the parsed `Content-Range` header, e.g. to check that a resumed download
+    /// starts at the requested offset and to learn the complete size of the resource.
+    ///
+    /// A header that is not a valid byte range is ignored.
+    ///
+    /// The response headers are only available if the collector also collects the headers,
+    /// e.g. `Collector::RamAndHeaders` or `Collector::FileAndHeaders`.
+    fn content_range(&self) -> Option<ContentRange>;
+
+    /// Returns the links of the `Link` headers by their relation type, e.g. the `next` and
+    /// `last` pages of a paginated API sending `<https://api/items?page=2>; rel="next"`.
+    ///
+    /// All `Link` headers and all links within a header are collected. A link with several
+    /// relation types like `rel="prev first"` is returned for each of them, the relation types
+    /// are in lowercase. If a relation type occurs more than once the first link is kept.
+    /// The URLs are returned as they were received, relative URLs are not resolved.
+    ///
+    /// The response headers are only available if the collector also collects the headers,
+    /// e.g. `Collector::RamAndHeaders` or `Collector::FileAndHeaders`.
+    fn links(&self) -> HashMap<String, String>;
+}
+
+impl<T> ResponseExt for Response<T> {
+    fn transfer_info(&self) -> Option<&TransferInfo> {
+        self.extensions().get::<TransferInfo>()
+    }
+
+    fn is_not_modified(&self) -> bool {
+        self.status() == StatusCode::NOT_MODIFIED
+    }
+
+    fn content_disposition_filename(&self) -> Option<String> {
+        let value = self.headers().get(CONTENT_DISPOSITION)?.to_str().ok()?;
+
+        let mut filename = None;
+        for (name, value) in header_params(value) {
+            if name.eq_ignore_ascii_case("filename*") {
+                if let Some(decoded) = decode_ext_value(&value) {
+                    filename = Some(decoded);
+                    break;
+                }
+            } else if name.eq_ignore_ascii_case("filename") {
+                filename = Some(value);
+            }
+        }
+
+        filename
+            .as_deref()
+            .and_then(|name| name.rsplit(|c: char| c == '/' || c == '\\').next())
+            .filter(|name| !name.is_empty() && *name != "." && *name != "..")
+            .map(str::to_string)
+    }
+
+    fn etag(&self) -> Option<&str> {
+        self.headers()
+            .get(ETAG)?
+            .to_str()
+            .ok()
+            .map(str::trim)
+            .filter(|etag| !etag.is_empty())
+    }
+
+    fn content_range(&self) -> Option<ContentRange> {
+        parse_content_range(self.headers().get(CONTENT_RANGE)?.to_str().ok()?)
+    }
+
+    fn links(&self) -> HashMap<String, String> {
+        let mut links = HashMap::new();
+        let values = self
+            .headers()
+            .get_all(LINK)
+            .iter()
+            .filter_map(|value| value.to_str().ok());
+
+        for link in values.flat_map(split_links) {
+            let (url, params) = match link
+                .trim()
+                .strip_prefix('<')
+                .and_then(|link| link.split_once('>'))
+            {
+                Some(link) => link,
+                None => continue,
+            };
+
+            let relations = header_params(params)
+                .into_iter()
+                .filter(|(name, _)| name.eq_ignore_ascii_case("rel"))
+                .map(|(_, relations)| relations);
+            for relations in relations {
+                for relation in relations.split_whitespace() {
+                    links
+                        .entry(relation.to_ascii_lowercase())
+                        .or_insert_with(|| url.trim().to_string());
+                }
+            }
+        }
+        links
+    }
+}
+
+/// Splits a `Link` header value into its links at the commas that are neither within
+/// the `<...>` URL nor within a quoted parameter value.
+fn split_links(value: &str) -> Vec<&str> {
+    let mut links = Vec::new();
+    let mut start = 0;
+    let mut in_url = false;
+    let mut in_quotes = false;
+    let mut escaped = false;
+
+    for (position, c) in value.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if in_quotes => escaped = true,
+            '"' if !in_url => in_quotes = !in_quotes,
+            '<' if !in_quotes => in_url = true,
+            '>' if !in_quotes => in_url = false,
+            ',' if !in_url && !in_quotes => {
+                links.push(&value[start..position]);
+                start = position + 1;
+            }
+            _ => {}
+        }
+    }
+    links.push(&value[start..]);
+    links
+}
+
+/// This is an extension trait for the `Response` returned by `perform()` when the body
+/// is collected in memory.
+pub trait ResponseBodyExt {
+    /// Splits the body of a `206 Partial Content` response into the ranges it holds, see
+    /// `HttpClient::ranges`.
+    ///
+    /// A `multipart/byteranges` body is split into its parts, any other partial body is a
+    /// single range described by the `Content-Range` header of the response. `None` is
+    /// returned if the server ignored the ranges and sent the whole resource, or if the
+    /// multipart body is malformed.
+    fn byte_ranges(&self) -> Option<Vec<ByteRange>>;
+}
+
+impl ResponseBodyExt for Response<Option<Vec<u8>>> {
+    fn byte_ranges(&self) -> Option<Vec<ByteRange>> {
+        if self.status() != StatusCode::PARTIAL_CONTENT {
+            return None;
+        }
+        let body = self.body().as_deref().unwrap_or_default();
+
+        let content_type = self
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        let is_multipart = content_type
+            .split(';')
+            .next()
+            .map(|mime| mime.trim().eq_ignore_ascii_case("multipart/byteranges"))
+            == Some(true);
+        if !is_multipart {
+            return Some(vec![ByteRange {
+                content_range: self.content_range(),
+                data: body.to_vec(),
+            }]);
+        }
+
+        let boundary = header_params(content_type)
+            .into_iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("boundary"))
+            .map(|(_, boundary)| boundary)?;
+        parse_multipart_byteranges(body, &boundary)
+    }
+}
+
+/// Parses a `Content-Range` header value like `bytes 0-99/1000` or `bytes */1000`.
+fn parse_content_range(value: &str) -> Option<ContentRange> {
+    let (unit, range) = value.trim().split_once(' ')?;
+    if !unit.eq_ignore_ascii_case("bytes") {
+        return None;
+    }
+
+    let (range, total) = range.trim().split_once('/')?;
+    let total = match total {
+        "*" => None,
+        total => Some(total.parse::<u64>().ok()?),
+    };
+
+    if range == "*" {
+        return total.map(|total| ContentRange::Unsatisfied { total });
+    }
+
+    let (start, end) = range.split_once('-')?;
+    let start = start.parse::<u64>().ok()?;
+    let end = end.parse::<u64>().ok()?;
+    if start > end || matches!(total, Some(total) if end >= total) {
+        return None;
+    }
+    Some(ContentRange::Bytes { start, end, total })
+}
+
+/// Splits a `multipart/byteranges` body into its parts, each of them has its own headers
+/// followed by an empty line and the data up to the next `--boundary` line.
+fn parse_multipart_byteranges(body: &[u8], boundary: &str) -> Option<Vec<ByteRange>> {
+    let delimiter = format!("--{}", boundary).into_bytes();
+    let mut position = find(body, &delimiter, 0)? + delimiter.len();
+    let mut parts = Vec::new();
+
+    loop {
+        if body[position..].starts_with(b"--") {
+            // The closing delimiter.
+            return Some(parts);
+        }
+        position = find(body, b"\r\n", position)? + 2;
+
+        let headers_end = find(body, b"\r\n\r\n", position - 2)?;
+        // A part without headers starts with the empty line right after the delimiter.
+        let headers = std::str::from_utf8(&body[position.min(headers_end)..headers_end]).ok()?;
+        let content_range = headers
+            .split("\r\n")
+            .filter_map(|line| line.split_once(':'))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-range"))
+            .and_then(|(_, value)| parse_content_range(value));
+
+        let data_start = headers_end + 4;
+        let mut next_delimiter = b"\r\n".to_vec();
+        next_delimiter.extend_from_slice(&delimiter);
+        let data_end = find(body, &next_delimiter, data_start)?;
+
+        parts.push(ByteRange {
+            content_range,
+            data: body[data_start..data_end].to_vec(),
+        });
+        position = data_end + next_delimiter.len();
+    }
+}
+
+/// Returns the position of the first occurrence of `needle` in `haystack` from `start` on.
+fn find(haystack: &[u8], needle: &[u8], start: usize) -> Option<usize> {
+    haystack
+        .get(start..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|position| start + position)
+}
+
+/// Splits the parameters of a header value like `attachment; filename="a;b.txt"` into
+/// name and value pairs, the quotes and escapes of quoted values are removed.
+fn header_params(value: &str) -> Vec<(String, String)> {
+    let mut params = Vec::new();
+    let mut chars = value.chars().skip_while(|c| *c != ';').peekable();
+
+    while chars.next() == Some(';') {
+        let mut name = String::new();
+        while let Some(c) = chars.next_if(|c| *c != '=' && *c != ';') {
+            name.push(c);
+        }
+
+        let mut value = String::new();
+        if chars.next_if_eq(&'=').is_some() {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+            if chars.next_if_eq(&'"').is_some() {
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => value.extend(chars.next()),
+                        c => value.push(c),
+                    }
+                }
+                while chars.next_if(|c| *c != ';').is_some() {}
+            } else {
+                while let Some(c) = chars.next_if(|c| *c != ';') {
+                    value.push(c);
+                }
+            }
+        }
+        params.push((name.trim().to_string(), value.trim().to_string()));
+    }
+    params
+}
+
+/// Decodes an RFC 5987 extended value of the form `charset'language'percent-encoded`.
+fn decode_ext_value(value: &str) -> Option<String> {
+    let mut parts = value.splitn(3, '\'');
+    let charset = parts.next()?;
+    let _language = parts.next()?;
+    let bytes = percent_decode(parts.next()?)?;
+
+    if charset.eq_ignore_ascii_case("utf-8") {
+        String::from_utf8(bytes).ok()
+    } else if charset.eq_ignore_ascii_case("iso-8859-1") {
+        Some(bytes.into_iter().map(char::from).collect())
+    } else {
+        None
+    }
+}
+
+fn percent_decode(value: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut input = value.bytes();
+
+    while let Some(b) = input.next() {
+        if b == b'%' {
+            let hex = [input.next()?, input.next()?];
+            let hex = std::str::from_utf8(&hex).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+        } else {
+            bytes.push(b);
+        }
+    }
+    Some(bytes)
+}
@@ -0,0 +1,97 @@
+use async_curl::Actor;
+use http::{
+    header::{ACCEPT_RANGES, CONTENT_LENGTH},
+    HeaderMap, Method, Request, Response, StatusCode,
+};
+
+use crate::{Collector, Error, HttpClient, ResponseExt};
+
+/// Whether the download of a resource can be resumed, see [`resume_support`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResumeSupport {
+    /// The server accepts byte ranges of the resource, so an interrupted download can be
+    /// resumed with `resume_from` instead of being restarted.
+    pub resumable: bool,
+    /// The complete size of the resource in bytes if the server told it.
+    pub total_size: Option<u64>,
+}
+
+/// Asks the server whether the download of the resource at the url can be resumed, so a
+/// download manager can decide between resuming a partial download and restarting it.
+///
+/// A HEAD request is sent first and a server announcing `Accept-Ranges: bytes` is resumable.
+/// Many servers honor ranges without announcing them, so otherwise the first byte of the
+/// resource is requested and a `206 Partial Content` response makes it resumable as well.
+pub async fn resume_support<A>(actor: A, url: &str) -> Result<ResumeSupport, Error<Collector>>
+where
+    A: Actor<Collector> + Clone,
+{
+    let head = HttpClient::new(Collector::RamAndHeaders(Vec::new(), Vec::new()))
+        .request(request(url, Method::HEAD)?)?
+        .nonblocking(actor.clone())
+        .perform()
+        .await?;
+
+    let head_size = if head.status().is_success() {
+        content_length(head.headers())
+    } else {
+        None
+    };
+    if head.status().is_success() && accepts_byte_ranges(&head) {
+        return Ok(ResumeSupport {
+            resumable: true,
+            total_size: head_size,
+        });
+    }
+
+    let probe = HttpClient::new(Collector::RamAndHeaders(Vec::new(), Vec::new()))
+        .range("0-0")?
+        .request(request(url, Method::GET)?)?
+        .nonblocking(actor)
+        .perform()
+        .await?;
+
+    if probe.status() == StatusCode::PARTIAL_CONTENT {
+        Ok(ResumeSupport {
+            resumable: true,
+            total_size: probe
+                .content_range()
+                .and_then(|range| range.total())
+                .or(head_size),
+        })
+    } else {
+        // The server ignored the range and sent the whole resource.
+        Ok(ResumeSupport {
+            resumable: false,
+            total_size: head_size.or_else(|| content_length(probe.headers())),
+        })
+    }
+}
+
+fn request(url: &str, method: Method) -> Result<Request<Option<Vec<u8>>>, Error<Collector>> {
+    Request::builder()
+        .uri(url)
+        .method(method)
+        .body(None)
+        .map_err(|e| Error::Http(e.to_string()))
+}
+
+fn accepts_byte_ranges(response: &Response<Option<Vec<u8>>>) -> bool {
+    response
+        .headers()
+        .get_all(ACCEPT_RANGES)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|unit| unit.trim().eq_ignore_ascii_case("bytes"))
+}
+
+fn content_length(headers: &HeaderMap) -> Option<u64> {
+    headers
+        .get(CONTENT_LENGTH)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
@@ -0,0 +1,232 @@
+use std::{
+    ffi::c_void,
+    sync::{Arc, Condvar, Mutex, PoisonError},
+};
+
+use async_curl::CurlActor;
+use curl::easy::Easy2;
+use http::{Method, Request, Response};
+
+use crate::{Collector, Error, HttpClient};
+
+/// The options applied to the handle before every request of a Session.
+type Configure =
+    dyn Fn(HttpClient<Collector>) -> Result<HttpClient<Collector>, Error<Collector>> + Send + Sync;
+
+/// Session is a client object that sends requests with a shared set of options, e.g.
+/// `session.get(url)` or `session.post(url, body)`.
+///
+/// The handles of finished requests are kept and reused for the next requests. A reused handle
+/// keeps its cookies, DNS cache and TLS session cache, only the options are reset before
+/// every request. The handles share one connection cache, so a connection that is kept alive
+/// by the server is reused by the next request to the same host.
+///
+/// A Session can be cloned and used from multiple tasks at the same time, the clones share the
+/// actor and the handles. Concurrent requests use different handles.
+#[derive(Clone)]
+pub struct Session {
+    actor: CurlActor<Collector>,
+    handles: Arc<Mutex<Vec<Easy2<Collector>>>>,
+    // Declared after the handles, so the handles are cleaned up before the share handle.
+    share: Arc<Share>,
+    configure: Arc<Configure>,
+}
+
+impl Session {
+    /// Creates a new Session that performs its requests with the actor.
+    pub fn new(actor: CurlActor<Collector>) -> Self {
+        Self {
+            actor,
+            handles: Arc::new(Mutex::new(Vec::new())),
+            share: Arc::new(Share::new()),
+            configure: Arc::new(Ok::<HttpClient<Collector>, Error<Collector>>),
+        }
+    }
+
+    /// Sets the options that are applied to every request of this Session,
+    /// e.g. `|client| client.connect_timeout(Duration::from_secs(5))`.
+    pub fn with_options<F>(mut self, configure: F) -> Self
+    where
+        F: Fn(HttpClient<Collector>) -> Result<HttpClient<Collector>, Error<Collector>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.configure = Arc::new(configure);
+        self
+    }
+
+    /// Sends a GET request to the URL.
+    pub async fn get(&self, url: &str) -> Result<Response<Option<Vec<u8>>>, Error<Collector>> {
+        self.send(Method::GET, url, None).await
+    }
+
+    /// Sends a POST request with the body to the URL.
+    pub async fn post(
+        &self,
+        url: &str,
+        body: Vec<u8>,
+    ) -> Result<Response<Option<Vec<u8>>>, Error<Collector>> {
+        self.send(Method::POST, url, Some(body)).await
+    }
+
+    /// Sends the request with the options of this Session.
+    ///
+    /// The response has the complete headers that were received.
+    pub async fn request(
+        &self,
+        request: Request<Option<Vec<u8>>>,
+    ) -> Result<Response<Option<Vec<u8>>>, Error<Collector>> {
+        let easy = self.handles.lock().unwrap().pop();
+        let easy = match easy {
+            Some(mut easy) => {
+                easy.reset();
+                *easy.get_mut() = Collector::RamAndHeaders(Vec::new(), Vec::new());
+                easy
+            }
+            None => Easy2::new(Collector::RamAndHeaders(Vec::new(), Vec::new())),
+        };
+        // Set again, since a reused handle was reset above.
+        self.share.attach(&easy)?;
+
+        let (response, easy) = (self.configure)(HttpClient::from_easy(easy))?
+            .request(request)?
+            .nonblocking(self.actor.clone())
+            .perform_and_keep_handle()
+            .await?;
+
+        self.handles.lock().unwrap().push(easy);
+        Ok(response)
+    }
+
+    /// Returns the number of handles that are kept for the next requests.
+    pub fn idle_handles(&self) -> usize {
+        self.handles.lock().unwrap().len()
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        url: &str,
+        body: Option<Vec<u8>>,
+    ) -> Result<Response<Option<Vec<u8>>>, Error<Collector>> {
+        let request = Request::builder()
+            .uri(url)
+            .method(method)
+            .body(body)
+            .map_err(|e| Error::Http(e.to_string()))?;
+        self.request(request).await
+    }
+}
+
+/// The number of `curl_lock_data` kinds that libcurl locks.
+const LOCK_DATA_KINDS: usize = 8;
+
+/// Share is a libcurl share handle that keeps the connection cache of the Session handles.
+///
+/// The transfers are performed on the thread of the actor while the handles are attached and
+/// cleaned up on the threads of the Session, so libcurl locks the shared data with `ShareLocks`.
+struct Share {
+    raw: *mut curl_sys::CURLSH,
+    locks: *mut ShareLocks,
+}
+
+// The shared data is only accessed by libcurl while it holds the locks of `ShareLocks`.
+unsafe impl Send for Share {}
+unsafe impl Sync for Share {}
+
+impl Share {
+    fn new() -> Self {
+        let locks = Box::into_raw(Box::new(ShareLocks {
+            locks: std::array::from_fn(|_| (Mutex::new(false), Condvar::new())),
+        }));
+        unsafe {
+            let raw = curl_sys::curl_share_init();
+            assert!(!raw.is_null());
+            let lock: curl_sys::curl_lock_function = lock_share;
+            let unlock: curl_sys::curl_unlock_function = unlock_share;
+            let userdata = locks as *mut c_void;
+            for rc in [
+                curl_sys::curl_share_setopt(
+                    raw,
+                    curl_sys::CURLSHOPT_SHARE,
+                    curl_sys::CURL_LOCK_DATA_CONNECT,
+                ),
+                curl_sys::curl_share_setopt(raw, curl_sys::CURLSHOPT_LOCKFUNC, lock),
+                curl_sys::curl_share_setopt(raw, curl_sys::CURLSHOPT_UNLOCKFUNC, unlock),
+                curl_sys::curl_share_setopt(raw, curl_sys::CURLSHOPT_USERDATA, userdata),
+            ] {
+                assert_eq!(rc, curl_sys::CURLSHE_OK);
+            }
+            Self { raw, locks }
+        }
+    }
+
+    /// Sets this share handle on the easy handle.
+    fn attach(&self, easy: &Easy2<Collector>) -> Result<(), Error<Collector>> {
+        // Both handles are alive, the Session drops its handles before the share handle.
+        let rc =
+            unsafe { curl_sys::curl_easy_setopt(easy.raw(), curl_sys::CURLOPT_SHARE, self.raw) };
+        if rc == curl_sys::CURLE_OK {
+            Ok(())
+        } else {
+            Err(Error::Curl(curl::Error::new(rc)))
+        }
+    }
+}
+
+impl Drop for Share {
+    fn drop(&mut self) {
+        let rc = unsafe { curl_sys::curl_share_cleanup(self.raw) };
+        // The share handle is not cleaned up while a handle of a dropped request future is
+        // still attached to it, that handle may lock it later, so the locks are kept as well.
+        if rc == curl_sys::CURLSHE_OK {
+            drop(unsafe { Box::from_raw(self.locks) });
+        }
+    }
+}
+
+/// ShareLocks has a lock for every kind of data of a share handle, libcurl locks and unlocks
+/// them from different callbacks, so they are not held by a guard.
+struct ShareLocks {
+    locks: [(Mutex<bool>, Condvar); LOCK_DATA_KINDS],
+}
+
+impl ShareLocks {
+    fn lock(&self, data: curl_sys::curl_lock_data) {
+        let (locked, unlocked) = &self.locks[data as usize % LOCK_DATA_KINDS];
+        let mut locked = locked.lock().unwrap_or_else(PoisonError::into_inner);
+        while *locked {
+            locked = unlocked
+                .wait(locked)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        *locked = true;
+    }
+
+    fn unlock(&self, data: curl_sys::curl_lock_data) {
+        let (locked, unlocked) = &self.locks[data as usize % LOCK_DATA_KINDS];
+        *locked.lock().unwrap_or_else(PoisonError::into_inner) = false;
+        unlocked.notify_one();
+    }
+}
+
+extern "C" fn lock_share(
+    _easy: *mut curl_sys::CURL,
+    data: curl_sys::curl_lock_data,
+    _access: curl_sys::curl_lock_access,
+    userptr: *mut c_void,
+) {
+    // The user data is the ShareLocks of the share handle, see `Share::new`.
+    let locks = unsafe { &*(userptr as *const ShareLocks) };
+    locks.lock(data);
+}
+
+extern "C" fn unlock_share(
+    _easy: *mut curl_sys::CURL,
+    data: curl_sys::curl_lock_data,
+    userptr: *mut c_void,
+) {
+    let locks = unsafe { &*(userptr as *const ShareLocks) };
+    locks.unlock(data);
+}
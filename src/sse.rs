@@ -0,0 +1,151 @@
+use std::{
+    collections::VecDeque,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use futures::Stream;
+use http::Response;
+
+use crate::{ChunkStream, Error, StreamingCollector};
+
+/// An event received from a Server-Sent Events (`text/event-stream`) response.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SseEvent {
+    /// The type of the event from the `event:` field, `None` means the default type `message`.
+    pub event: Option<String>,
+    /// The `data:` fields of the event joined with a newline.
+    pub data: String,
+    /// The last event id from the `id:` field.
+    pub id: Option<String>,
+    /// The reconnection time in milliseconds from the `retry:` field.
+    pub retry: Option<u64>,
+}
+
+/// SseDecoder parses the chunks of a Server-Sent Events stream into events.
+///
+/// The chunks can be split anywhere, an incomplete line is buffered until the rest of it is received.
+/// Comment lines starting with `:` are ignored.
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    buffer: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+    id: Option<String>,
+    retry: Option<u64>,
+}
+
+impl SseDecoder {
+    /// Creates a SseDecoder with nothing buffered.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses the next chunk of the stream and returns the events completed by it.
+    pub fn decode(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches('\n').trim_end_matches('\r');
+
+            if let Some(event) = self.process_line(line) {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    fn process_line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => self.data.push(value.to_string()),
+            "id" if !value.contains('\0') => self.id = Some(value.to_string()),
+            "retry" => {
+                if let Ok(retry) = value.parse::<u64>() {
+                    self.retry = Some(retry);
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// An empty line ends the event, it is only dispatched if it has data.
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        let event = self.event.take();
+        let retry = self.retry.take();
+        if self.data.is_empty() {
+            return None;
+        }
+
+        Some(SseEvent {
+            event,
+            data: self.data.drain(..).collect::<Vec<_>>().join("\n"),
+            // The last event id is kept for the following events.
+            id: self.id.clone(),
+            retry,
+        })
+    }
+}
+
+/// SseStream yields the events of a Server-Sent Events response received through a ChunkStream.
+///
+/// An event that is not terminated by an empty line when the response ends is discarded.
+pub struct SseStream {
+    chunks: ChunkStream,
+    decoder: SseDecoder,
+    events: VecDeque<SseEvent>,
+}
+
+impl SseStream {
+    /// Creates the SseStream from the ChunkStream returned by `AsyncPerform::into_chunk_stream`.
+    pub fn new(chunks: ChunkStream) -> Self {
+        Self {
+            chunks,
+            decoder: SseDecoder::new(),
+            events: VecDeque::new(),
+        }
+    }
+
+    /// Returns the status and the headers of the response once the stream has ended
+    /// and the transfer was successful.
+    pub fn response(&self) -> Option<&Response<()>> {
+        self.chunks.response()
+    }
+}
+
+impl Stream for SseStream {
+    type Item = Result<SseEvent, Error<StreamingCollector>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return Poll::Ready(Some(Ok(event)));
+            }
+
+            match ready!(Pin::new(&mut self.chunks).poll_next(cx)) {
+                Some(Ok(chunk)) => {
+                    let events = self.decoder.decode(&chunk);
+                    self.events.extend(events);
+                }
+                Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
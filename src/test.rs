@@ -1,8 +1,20 @@
 mod asynchronous;
+mod auth;
 mod cancel;
+mod conditional;
 mod download;
 mod get;
+mod har;
 mod headers;
+#[cfg(feature = "json")]
+mod ndjson;
+mod options;
 mod post;
+mod proxy;
+mod redirect;
+mod request;
+mod resume;
+mod session;
+mod sse;
 mod test_setup;
 mod upload;
@@ -0,0 +1,173 @@
+use async_curl::CurlActor;
+use http::{Method, Request, StatusCode};
+use test_case::test_case;
+use url::Url;
+use wiremock::matchers::header;
+
+use crate::collector::Collector;
+use crate::error::Error;
+use crate::http_client::{AuthMethod, HttpClient};
+use crate::test::test_setup::{setup_test_environment_with, MockResponder, ResponderType};
+
+#[tokio::test]
+async fn test_basic_auth() {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) =
+        setup_test_environment_with(header("authorization", "Basic dXNlcjpwYXNz"), responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .basic_auth("user", Some("pass"))
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        *response.body().as_ref().unwrap(),
+        "test body".as_bytes().to_vec()
+    );
+}
+
+#[test]
+fn test_basic_auth_username_with_colon() {
+    let result = HttpClient::new(Collector::Ram(Vec::new())).basic_auth("us:er", Some("pass"));
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_auth_methods() {
+    let client = HttpClient::new(Collector::Ram(Vec::new()))
+        .auth_methods(&[AuthMethod::Digest, AuthMethod::Basic]);
+
+    assert!(client.is_ok());
+}
+
+#[test]
+fn test_sso_auth() {
+    match HttpClient::new(Collector::Ram(Vec::new())).sso_auth() {
+        Ok(_) => {}
+        // libcurl was built without Negotiate and NTLM.
+        Err(Error::Curl(e)) => println!("Single sign-on is not supported: {}", e),
+        Err(e) => panic!("unexpected error {:?}", e),
+    }
+}
+
+#[test_case("user:pass", "Basic dXNlcjpwYXNz" ; "plain")]
+#[test_case("us%40er:p%3Ass", "Basic dXNAZXI6cDpzcw==" ; "percent encoded")]
+#[tokio::test]
+async fn test_basic_auth_in_url(userinfo: &str, authorization: &str) {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) =
+        setup_test_environment_with(header("authorization", authorization), responder).await;
+    let target_url =
+        format!("{}/test", server.uri()).replacen("http://", &format!("http://{}@", userinfo), 1);
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[test]
+fn test_xoauth2_bearer_with_username() {
+    let client = HttpClient::new(Collector::Ram(Vec::new()))
+        .username("user@example.com")
+        .unwrap()
+        .xoauth2_bearer("token");
+
+    assert!(client.is_ok());
+}
+
+#[tokio::test]
+async fn test_xoauth2_bearer() {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) =
+        setup_test_environment_with(header("authorization", "Bearer abc.def"), responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .xoauth2_bearer("abc.def")
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_xoauth2_bearer_keeps_http_auth() {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) =
+        setup_test_environment_with(header("authorization", "Basic dXNlcjpwYXNz"), responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .auth_methods(&[AuthMethod::Basic])
+        .unwrap()
+        .username("user")
+        .unwrap()
+        .password("pass")
+        .unwrap()
+        .xoauth2_bearer("abc.def")
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    // The Basic authentication chosen before is not replaced by the bearer token.
+    assert_eq!(response.status(), StatusCode::OK);
+}
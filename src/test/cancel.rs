@@ -1,11 +1,21 @@
-use std::{fs::File, time::Duration};
+use std::{
+    fs::{self, File},
+    io::{BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener},
+    thread,
+    time::{Duration, Instant},
+};
 
 use async_curl::CurlActor;
 use http::{Method, Request};
+use tokio_util::sync::CancellationToken;
 use url::Url;
 
 use crate::{
-    collector::{AbortPerform, Collector, FileInfo},
+    collector::{
+        AbortPerform, AfterFirstByteTimeout, Cancellable, Collector, ExtendedHandler, FileInfo,
+    },
+    error::Error,
     http_client::{Bps, HttpClient},
     test::test_setup::{setup_test_environment, MockResponder, ResponderType},
 };
@@ -110,3 +120,398 @@ async fn test_download_was_not_cancelled() {
     // If not cancelled, the file downloaded must be completed.
     assert!(downloaded_file.metadata().unwrap().len() == mock_file.len() as u64);
 }
+
+#[tokio::test]
+async fn test_cancelled_download_was_deleted() {
+    let responder = MockResponder::new(ResponderType::File);
+    let (server, tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let save_to = tempdir.path().join("downloaded_file.jpg");
+    let actor = CurlActor::new();
+    let abort = AbortPerform::new();
+
+    let abort_listener = abort.clone();
+    let file_info = FileInfo::path(save_to.clone())
+        .with_perform_aborter(abort_listener)
+        .delete_on_error(true);
+    let handle = tokio::spawn(async move {
+        let collector = Collector::File(file_info);
+        let request = Request::builder()
+            .uri(target_url.as_str())
+            .method(Method::GET)
+            .body(None)
+            .unwrap();
+
+        HttpClient::new(collector)
+            .progress(true)
+            .unwrap()
+            .download_speed(Bps::from(5000000))
+            .unwrap()
+            .request(request)
+            .unwrap()
+            .nonblocking(actor)
+            .perform()
+            .await
+    });
+
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        let mut abort = abort.lock().unwrap();
+        *abort = true;
+    });
+
+    let response = handle.await.unwrap();
+    println!("Response: {:?}", response);
+
+    assert!(response.is_err());
+    assert!(!save_to.exists());
+}
+
+#[tokio::test]
+async fn test_download_cancelled_by_dropping_the_future() {
+    let responder = MockResponder::new(ResponderType::File);
+    let (server, tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let save_to = tempdir.path().join("downloaded_file.jpg");
+    let actor = CurlActor::new();
+    let collector = Collector::File(FileInfo::path(save_to.clone()));
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let perform = HttpClient::new(collector)
+        .download_speed(Bps::from(5000000))
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform();
+
+    // The perform future is dropped once the timeout elapses.
+    let result = tokio::time::timeout(Duration::from_millis(500), perform).await;
+    assert!(result.is_err());
+
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let size_after_drop = fs::metadata(&save_to).unwrap().len();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let mock_file = include_bytes!("sample.jpg");
+    // The transfer must have stopped partially downloaded.
+    assert_eq!(fs::metadata(&save_to).unwrap().len(), size_after_drop);
+    assert!(size_after_drop < mock_file.len() as u64);
+}
+
+#[tokio::test]
+async fn test_ram_download_cancelled_by_dropping_the_future() {
+    let responder = MockResponder::new(ResponderType::File);
+    let (server, _tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    // A bare Collector::Ram cannot be aborted, it has to be wrapped in Cancellable.
+    assert!(Collector::Ram(Vec::new()).perform_aborter().is_none());
+    let mut collector = Cancellable::new(Collector::Ram(Vec::new()), CancellationToken::new());
+    let abort = collector.perform_aborter().unwrap();
+
+    let actor = CurlActor::new();
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let perform = HttpClient::new(collector)
+        .download_speed(Bps::from(5000000))
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform();
+
+    // The perform future is dropped once the timeout elapses.
+    let result = tokio::time::timeout(Duration::from_millis(500), perform).await;
+    assert!(result.is_err());
+    assert!(*abort.lock().unwrap());
+}
+
+/// Starts a server that sends the headers and the first byte of the body, then stalls before
+/// sending the rest of it.
+fn start_stalling_server(stall: Duration) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    thread::spawn(move || {
+        for client in listener.incoming() {
+            let mut client = client.unwrap();
+            let mut reader = BufReader::new(client.try_clone().unwrap());
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                    break;
+                }
+            }
+
+            let _ = client.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 9\r\n\r\nt");
+            thread::sleep(stall);
+            let _ = client.write_all(b"est body");
+        }
+    });
+
+    address
+}
+
+#[tokio::test]
+async fn test_after_first_byte_timeout() {
+    let address = start_stalling_server(Duration::from_secs(10));
+    let target_url = format!("http://{}/test", address);
+
+    let actor = CurlActor::new();
+    let collector =
+        AfterFirstByteTimeout::new(Collector::Ram(Vec::new()), Duration::from_millis(500));
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let started = Instant::now();
+    let result = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await;
+
+    println!("Result: {:?}", result);
+    assert!(matches!(result, Err(Error::Perform(_))));
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn test_after_first_byte_timeout_not_exceeded() {
+    let address = start_stalling_server(Duration::from_millis(100));
+    let target_url = format!("http://{}/test", address);
+
+    let collector = AfterFirstByteTimeout::new(Collector::Ram(Vec::new()), Duration::from_secs(5));
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .blocking()
+        .perform()
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.body().as_deref(), Some("test body".as_bytes()));
+}
+
+#[test]
+fn test_after_first_byte_timeout_blocking() {
+    let address = start_stalling_server(Duration::from_secs(10));
+    let target_url = format!("http://{}/test", address);
+
+    let collector =
+        AfterFirstByteTimeout::new(Collector::Ram(Vec::new()), Duration::from_millis(500));
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let started = Instant::now();
+    let result = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .blocking()
+        .send_request();
+
+    assert!(matches!(result, Err(Error::Perform(_))));
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn test_timeout_keeps_received_headers() {
+    let address = start_stalling_server(Duration::from_secs(10));
+    let target_url = format!("http://{}/test", address);
+
+    let collector = Collector::RamAndHeaders(Vec::new(), Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let result = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .timeout(Duration::from_millis(500))
+        .unwrap()
+        .headers_on_timeout(true)
+        .unwrap()
+        .blocking()
+        .perform();
+
+    match result {
+        Err(Error::Timeout {
+            error,
+            status_line,
+            headers,
+        }) => {
+            assert!(error.is_operation_timedout());
+            assert_eq!(status_line.as_deref(), Some("HTTP/1.1 200 OK"));
+            let headers = headers.unwrap();
+            assert_eq!(headers.get("content-length").unwrap(), "9");
+        }
+        result => panic!("expected a timeout with headers, got {:?}", result),
+    }
+}
+
+#[test]
+fn test_timeout_before_headers_has_no_headers() {
+    // The connection is accepted by the backlog of the listener but nothing is ever sent.
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let target_url = format!("http://{}/test", listener.local_addr().unwrap());
+
+    let collector = Collector::RamAndHeaders(Vec::new(), Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let result = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .timeout(Duration::from_millis(500))
+        .unwrap()
+        .headers_on_timeout(true)
+        .unwrap()
+        .blocking()
+        .perform();
+
+    assert!(matches!(
+        result,
+        Err(Error::Timeout {
+            status_line: None,
+            headers: None,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_timeout_without_headers_on_timeout() {
+    let address = start_stalling_server(Duration::from_secs(10));
+    let target_url = format!("http://{}/test", address);
+
+    let collector = Collector::RamAndHeaders(Vec::new(), Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let result = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .timeout(Duration::from_millis(500))
+        .unwrap()
+        .blocking()
+        .perform();
+
+    assert!(matches!(result, Err(Error::Perform(_))));
+}
+
+#[tokio::test]
+async fn test_download_cancelled_by_token() {
+    let address = start_stalling_server(Duration::from_secs(10));
+    let target_url = format!("http://{}/test", address);
+    let tempdir = tempfile::TempDir::with_prefix_in("test", "./").unwrap();
+
+    let token = CancellationToken::new();
+    let collector = Collector::File(
+        FileInfo::path(tempdir.path().join("downloaded_file.txt"))
+            .with_cancellation_token(token.child_token()),
+    );
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        token.cancel();
+    });
+
+    let started = Instant::now();
+    let result = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(CurlActor::new())
+        .perform()
+        .await;
+
+    println!("Result: {:?}", result);
+    assert!(matches!(result, Err(Error::Aborted)));
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn test_cancellable_collector() {
+    let address = start_stalling_server(Duration::from_secs(10));
+    let target_url = format!("http://{}/test", address);
+
+    let token = CancellationToken::new();
+    let collector = Cancellable::new(Collector::Ram(Vec::new()), token.clone());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(300));
+        token.cancel();
+    });
+
+    let started = Instant::now();
+    let result = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .blocking()
+        .perform();
+
+    assert!(matches!(result, Err(Error::Aborted)));
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn test_cancellable_collector_not_cancelled() {
+    let address = start_stalling_server(Duration::from_millis(100));
+    let target_url = format!("http://{}/test", address);
+
+    let collector = Cancellable::new(Collector::Ram(Vec::new()), CancellationToken::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .blocking()
+        .perform()
+        .unwrap();
+
+    assert_eq!(response.body().as_deref(), Some("test body".as_bytes()));
+}
@@ -0,0 +1,121 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use async_curl::CurlActor;
+use http::{Method, Request, StatusCode};
+use test_case::test_case;
+use url::Url;
+use wiremock::{matchers::header, ResponseTemplate};
+
+use crate::collector::Collector;
+use crate::http_client::HttpClient;
+use crate::response::ResponseExt;
+use crate::test::test_setup::setup_test_environment_with;
+
+// The time of the conditional requests, which libcurl sends as this HTTP-date.
+const MODIFIED_SINCE_SECS: u64 = 1_700_000_000;
+const MODIFIED_SINCE_DATE: &str = "Tue, 14 Nov 2023 22:13:20 GMT";
+
+#[tokio::test]
+async fn test_if_modified_since() {
+    let (server, _tempdir) = setup_test_environment_with(
+        header("if-modified-since", MODIFIED_SINCE_DATE),
+        ResponseTemplate::new(StatusCode::NOT_MODIFIED.as_u16()),
+    )
+    .await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .if_modified_since(UNIX_EPOCH + Duration::from_secs(MODIFIED_SINCE_SECS))
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(*response.body(), None);
+}
+
+#[tokio::test]
+async fn test_condition_unmet_sync() {
+    let (server, _tempdir) = setup_test_environment_with(
+        header("if-modified-since", MODIFIED_SINCE_DATE),
+        ResponseTemplate::new(StatusCode::NOT_MODIFIED.as_u16()),
+    )
+    .await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .if_modified_since(UNIX_EPOCH + Duration::from_secs(MODIFIED_SINCE_SECS))
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .blocking()
+        .perform()
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert!(response.is_not_modified());
+    assert!(response.transfer_info().unwrap().condition_unmet);
+}
+
+#[test]
+fn test_if_modified_since_before_epoch() {
+    let result = HttpClient::new(Collector::Ram(Vec::new()))
+        .if_modified_since(UNIX_EPOCH - Duration::from_secs(1));
+
+    assert!(result.is_err());
+}
+
+#[test_case(r#""abc""#, r#""abc""# ; "strong")]
+#[test_case(r#"W/"abc""#, r#"W/"abc""# ; "weak")]
+#[test_case("abc", r#""abc""# ; "bare")]
+#[tokio::test]
+async fn test_if_none_match(etag: &str, expected: &'static str) {
+    let responder =
+        ResponseTemplate::new(StatusCode::NOT_MODIFIED.as_u16()).insert_header("ETag", expected);
+    let (server, _tempdir) =
+        setup_test_environment_with(header("if-none-match", expected), responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::RamAndHeaders(Vec::new(), Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .if_none_match(etag)
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert!(response.is_not_modified());
+    assert_eq!(*response.body(), None);
+    assert_eq!(response.etag(), Some(expected));
+}
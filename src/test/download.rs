@@ -1,14 +1,32 @@
 use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use async_curl::CurlActor;
-use http::{Method, Request, StatusCode};
+use curl::easy::Handler;
+use futures::StreamExt;
+use http::{
+    header::{CONTENT_LENGTH, DATE},
+    Method, Request, StatusCode,
+};
 use test_case::test_case;
-use tokio::sync::mpsc::channel;
+use tokio::io::AsyncReadExt;
+use tokio::sync::{mpsc::channel, watch};
 use url::Url;
+use wiremock::{matchers::method, ResponseTemplate};
 
-use crate::collector::{Collector, FileInfo};
+use crate::collector::{
+    BackgroundFileCollector, Collector, FileInfo, StreamingCollector, TeeCollector, TransferSpeed,
+};
+use crate::error::Error;
 use crate::http_client::{Bps, BytesOffset, HttpClient};
-use crate::test::test_setup::{setup_test_environment, MockResponder, ResponderType};
+use crate::parallel::parallel_download;
+use crate::response::{ContentRange, ResponseExt};
+use crate::test::test_setup::{
+    setup_test_environment, setup_test_environment_with, start_raw_server, MockResponder,
+    ResponderType,
+};
 
 #[tokio::test]
 async fn test_download() {
@@ -158,6 +176,108 @@ async fn test_download_with_transfer_speed_sender() {
     handle.abort();
 }
 
+#[test]
+fn test_download_sync_with_transfer_speed_sender() {
+    // No tokio runtime is running in this test.
+    let address = start_raw_server(
+        b"HTTP/1.1 200 OK\r\nContent-Length: 9\r\nConnection: close\r\n\r\ntest body".to_vec(),
+    );
+    let target_url = format!("http://{}/test", address);
+    let tempdir = tempfile::TempDir::with_prefix_in("test", "./").unwrap();
+    let save_to = tempdir.path().join("downloaded_file.txt");
+
+    let (tx, mut rx) = channel(1);
+
+    let file_info = FileInfo::path(save_to.clone()).with_transfer_speed_sender(tx);
+    let collector = Collector::File(file_info);
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .blocking()
+        .perform()
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(fs::read(save_to).unwrap(), b"test body");
+    assert!(rx.try_recv().is_ok());
+}
+
+#[tokio::test]
+async fn test_download_non_utf8_header() {
+    // The file name of the Content-Disposition is Latin-1, "caf\xe9.txt".
+    let address = start_raw_server(
+        b"HTTP/1.1 200 OK\r\nContent-Disposition: attachment; filename=\"caf\xe9.txt\"\r\n\
+          X-Test: ok\r\nContent-Length: 9\r\nConnection: close\r\n\r\ntest body"
+            .to_vec(),
+    );
+    let target_url = format!("http://{}/test", address);
+    let tempdir = tempfile::TempDir::with_prefix_in("test", "./").unwrap();
+    let save_to = tempdir.path().join("downloaded_file.txt");
+
+    let collector = Collector::File(FileInfo::path(save_to.clone()));
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(CurlActor::new())
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get("x-test").unwrap(), "ok");
+    assert_eq!(
+        response
+            .headers()
+            .get("content-disposition")
+            .unwrap()
+            .as_bytes(),
+        b"attachment; filename=\"caf\xe9.txt\""
+    );
+    assert_eq!(fs::read(save_to).unwrap(), b"test body");
+}
+
+#[tokio::test]
+async fn test_download_average_speed() {
+    let responder = MockResponder::new(ResponderType::File);
+    let (server, _tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    let transfer_info = response.transfer_info().unwrap();
+    assert!(transfer_info.average_download_speed > 0);
+    assert_eq!(transfer_info.average_upload_speed, 0);
+}
+
 #[tokio::test]
 async fn test_download_with_headers() {
     let responder = MockResponder::new(ResponderType::File);
@@ -187,3 +307,624 @@ async fn test_download_with_headers() {
     assert_eq!(fs::read(save_to).unwrap(), include_bytes!("sample.jpg"));
     assert!(!response.headers().is_empty());
 }
+
+#[tokio::test]
+async fn test_parallel_download() {
+    let responder = MockResponder::new(ResponderType::File);
+    let (server, tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let save_to = tempdir.path().join("downloaded_file.jpg");
+    let actor = CurlActor::new();
+
+    parallel_download(actor, target_url.as_str(), save_to.clone(), 4)
+        .await
+        .unwrap();
+
+    assert_eq!(fs::read(save_to).unwrap(), include_bytes!("sample.jpg"));
+}
+
+#[tokio::test]
+async fn test_parallel_download_not_found() {
+    let (server, tempdir) = setup_test_environment_with(
+        method("GET"),
+        ResponseTemplate::new(404).set_body_bytes("not found".as_bytes()),
+    )
+    .await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let save_to = tempdir.path().join("downloaded_file.jpg");
+    let actor = CurlActor::new();
+
+    let result = parallel_download(actor, target_url.as_str(), save_to.clone(), 4).await;
+
+    println!("Result: {:?}", result);
+    assert!(matches!(result, Err(Error::Other(_))));
+    assert!(!save_to.exists());
+}
+
+#[tokio::test]
+async fn test_download_with_background_file_collector() {
+    let responder = MockResponder::new(ResponderType::File);
+    let (server, tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let save_to = tempdir.path().join("downloaded_file.jpg");
+    let actor = CurlActor::new();
+
+    let download = HttpClient::new(BackgroundFileCollector::path(save_to.clone()))
+        .request(
+            Request::builder()
+                .uri(target_url.as_str())
+                .method(Method::GET)
+                .body(None)
+                .unwrap(),
+        )
+        .unwrap()
+        .nonblocking(actor)
+        .perform();
+
+    let get = HttpClient::new(Collector::Ram(Vec::new()))
+        .request(
+            Request::builder()
+                .uri(target_url.as_str())
+                .method(Method::GET)
+                .header("range", "bytes=0-99")
+                .body(None)
+                .unwrap(),
+        )
+        .unwrap()
+        .nonblocking(CurlActor::new())
+        .perform();
+
+    let (download, get) = tokio::join!(download, get);
+
+    let download = download.unwrap();
+    println!("Response: {:?}", download);
+    assert_eq!(download.status(), StatusCode::OK);
+    assert_eq!(*download.body(), None);
+    assert_eq!(fs::read(save_to).unwrap(), include_bytes!("sample.jpg"));
+
+    let get = get.unwrap();
+    assert_eq!(get.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(
+        *get.body().as_ref().unwrap(),
+        include_bytes!("sample.jpg")[..100].to_vec()
+    );
+}
+
+#[tokio::test]
+async fn test_download_with_tee_collector() {
+    let responder = MockResponder::new(ResponderType::File);
+    let (server, tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let save_to = tempdir.path().join("downloaded_file.jpg");
+    let copy = Arc::new(Mutex::new(Vec::<u8>::new()));
+    let collector = TeeCollector::new()
+        .with_sink(fs::File::create(&save_to).unwrap())
+        .with_shared_sink(copy.clone());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(CurlActor::new())
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(!response.headers().is_empty());
+    assert_eq!(fs::read(&save_to).unwrap(), include_bytes!("sample.jpg"));
+    assert_eq!(*copy.lock().unwrap(), include_bytes!("sample.jpg"));
+}
+
+/// A sink that fails every write.
+struct FailingSink;
+
+impl std::io::Write for FailingSink {
+    fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "sink failed",
+        ))
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_download_with_tee_collector_failing_sink() {
+    let responder = MockResponder::new(ResponderType::File);
+    let (server, tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let save_to = tempdir.path().join("downloaded_file.jpg");
+    let collector = TeeCollector::new()
+        .with_sink(fs::File::create(&save_to).unwrap())
+        .with_sink(FailingSink);
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let result = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(CurlActor::new())
+        .perform()
+        .await;
+
+    assert!(matches!(result, Err(Error::Perform(_))));
+    // The transfer was aborted at the first chunk.
+    assert!(fs::read(&save_to).unwrap().len() < include_bytes!("sample.jpg").len());
+}
+
+#[tokio::test]
+async fn test_download_into_chunk_stream() {
+    let responder = MockResponder::new(ResponderType::File);
+    let (server, _tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let mut stream = HttpClient::new(StreamingCollector::new())
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .into_chunk_stream();
+
+    let mut body = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        body.extend_from_slice(&chunk.unwrap());
+    }
+
+    let response = stream.response().unwrap();
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(!response.headers().is_empty());
+    assert_eq!(body, include_bytes!("sample.jpg"));
+}
+
+#[test_case(include_bytes!("sample.jpg").len(), true ; "limit is the body size")]
+#[test_case(include_bytes!("sample.jpg").len() - 1, false ; "body exceeds the limit")]
+#[test_case(1024, false ; "small limit")]
+#[tokio::test]
+async fn test_download_into_chunk_stream_with_max_body_size(max_size: usize, complete: bool) {
+    let responder = MockResponder::new(ResponderType::File);
+    let (server, _tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let mut stream = HttpClient::new(StreamingCollector::new().with_max_body_size(max_size))
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .into_chunk_stream();
+
+    let mut body = Vec::new();
+    let mut error = None;
+    while let Some(chunk) = stream.next().await {
+        match chunk {
+            Ok(chunk) => body.extend_from_slice(&chunk),
+            Err(e) => error = Some(e),
+        }
+    }
+
+    assert!(body.len() <= max_size);
+    if complete {
+        assert!(error.is_none());
+        assert_eq!(body, include_bytes!("sample.jpg"));
+    } else {
+        assert!(matches!(error, Some(Error::BodyTooLarge(size)) if size == max_size));
+        assert!(stream.response().is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_download_into_async_read() {
+    let responder = MockResponder::new(ResponderType::File);
+    let (server, _tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let mut reader = HttpClient::new(StreamingCollector::new())
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .into_async_read();
+
+    let mut body = Vec::new();
+    reader.read_to_end(&mut body).await.unwrap();
+
+    let response = reader.response().unwrap();
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(body, include_bytes!("sample.jpg"));
+}
+
+#[tokio::test]
+async fn test_download_into_async_read_error() {
+    let responder = MockResponder::new(ResponderType::File);
+    let (server, _tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let mut reader = HttpClient::new(StreamingCollector::new())
+        .max_filesize(1024)
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .into_async_read();
+
+    let mut body = Vec::new();
+    let result = reader.read_to_end(&mut body).await;
+
+    println!("Result: {:?}", result);
+    assert!(result.is_err());
+    assert!(reader.response().is_none());
+}
+
+#[tokio::test]
+async fn test_download_file_has_server_headers() {
+    let responder = MockResponder::new(ResponderType::File);
+    let (server, tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let save_to = tempdir.path().join("downloaded_file.jpg");
+    let actor = CurlActor::new();
+    let collector = Collector::File(FileInfo::path(save_to.clone()));
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().contains_key(DATE));
+    assert_eq!(
+        response.headers().get(CONTENT_LENGTH).unwrap(),
+        include_bytes!("sample.jpg").len().to_string().as_str()
+    );
+    assert_eq!(fs::read(save_to).unwrap(), include_bytes!("sample.jpg"));
+}
+
+#[tokio::test]
+async fn test_download_with_fsync_on_complete() {
+    let responder = MockResponder::new(ResponderType::File);
+    let (server, tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let save_to = tempdir.path().join("downloaded_file.jpg");
+    let actor = CurlActor::new();
+    let collector = Collector::File(FileInfo::path(save_to.clone()).fsync_on_complete(true));
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(fs::read(save_to).unwrap(), include_bytes!("sample.jpg"));
+}
+
+#[tokio::test]
+async fn test_download_with_atomic_rename() {
+    let responder = MockResponder::new(ResponderType::File);
+    let (server, tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let save_to = tempdir.path().join("downloaded_file.jpg");
+    let part = FileInfo::part_path(&save_to);
+    assert_eq!(part, tempdir.path().join("downloaded_file.jpg.part"));
+
+    let seen_during_transfer = Arc::new(Mutex::new(Vec::new()));
+    let seen = seen_during_transfer.clone();
+    let (final_path, part_path) = (save_to.clone(), part.clone());
+    let actor = CurlActor::new();
+    let collector = Collector::File(
+        FileInfo::path(save_to.clone())
+            .with_atomic_rename(true)
+            .with_progress_callback(move |_, _| {
+                seen.lock()
+                    .unwrap()
+                    .push((final_path.exists(), part_path.exists()));
+                true
+            }),
+    );
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    let seen = seen_during_transfer.lock().unwrap();
+    assert!(!seen.is_empty());
+    // Only the temporary file exists while the body is received.
+    assert!(seen.iter().all(|&exists| exists == (false, true)));
+    assert_eq!(fs::read(&save_to).unwrap(), include_bytes!("sample.jpg"));
+    assert!(!part.exists());
+}
+
+#[tokio::test]
+async fn test_download_with_atomic_rename_removes_part_on_error() {
+    let responder = MockResponder::new(ResponderType::File);
+    let (server, tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let save_to = tempdir.path().join("downloaded_file.jpg");
+    let actor = CurlActor::new();
+    let collector = Collector::File(
+        FileInfo::path(save_to.clone())
+            .with_atomic_rename(true)
+            .with_progress_callback(|_, _| false),
+    );
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let result = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await;
+
+    assert!(result.is_err());
+    assert!(!save_to.exists());
+    assert!(!FileInfo::part_path(&save_to).exists());
+}
+
+#[tokio::test]
+async fn test_download_empty_body_creates_file() {
+    let responder = ResponseTemplate::new(StatusCode::NO_CONTENT.as_u16());
+    let (server, tempdir) = setup_test_environment_with(method("GET"), responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let save_to = tempdir.path().join("downloaded_file.jpg");
+    let actor = CurlActor::new();
+    let collector = Collector::File(FileInfo::path(save_to.clone()));
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert!(fs::read(save_to).unwrap().is_empty());
+}
+
+#[test_case(false ; "decompressed")]
+#[test_case(true ; "compressed")]
+#[tokio::test]
+async fn test_download_store_compressed(store_compressed: bool) {
+    // "test body test body test body test body" compressed with gzip.
+    let compressed = [
+        31, 139, 8, 0, 0, 0, 0, 0, 2, 3, 43, 73, 45, 46, 81, 72, 202, 79, 169, 84, 40, 193, 199, 2,
+        0, 120, 141, 216, 231, 39, 0, 0, 0,
+    ];
+    let responder = ResponseTemplate::new(200)
+        .insert_header("Content-Encoding", "gzip")
+        .set_body_bytes(compressed.as_slice());
+    let (server, tempdir) = setup_test_environment_with(method("GET"), responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let save_to = tempdir.path().join("downloaded_file.txt");
+    let actor = CurlActor::new();
+    let collector =
+        Collector::File(FileInfo::path(save_to.clone()).store_compressed(store_compressed));
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .accept_encoding("gzip")
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    if store_compressed {
+        assert_eq!(fs::read(save_to).unwrap(), compressed.to_vec());
+    } else {
+        assert_eq!(
+            fs::read(save_to).unwrap(),
+            "test body test body test body test body".as_bytes()
+        );
+    }
+}
+
+#[test]
+fn test_current_offset() {
+    let tempdir = tempfile::TempDir::with_prefix_in("test", "./").unwrap();
+    let partial_file = tempdir.path().join("partial_file.jpg");
+
+    assert_eq!(*FileInfo::current_offset(&partial_file).unwrap(), 0);
+
+    fs::write(&partial_file, &include_bytes!("sample.jpg")[..4500]).unwrap();
+    assert_eq!(*FileInfo::current_offset(&partial_file).unwrap(), 4500);
+}
+
+#[test]
+fn test_transfer_speed_watch_holds_latest() {
+    let tempdir = tempfile::TempDir::with_prefix_in("test", "./").unwrap();
+    let save_to = tempdir.path().join("downloaded_file.jpg");
+
+    let (tx, mut rx) = watch::channel(TransferSpeed::from(0u64));
+    let last_speed = Arc::new(Mutex::new(None));
+    let last_speed_in_callback = last_speed.clone();
+    let file_info = FileInfo::path(save_to)
+        .with_transfer_speed_watch(tx)
+        .with_progress_callback(move |speed, _| {
+            *last_speed_in_callback.lock().unwrap() = Some(speed.as_bytes_per_sec());
+            true
+        });
+    let mut collector = Collector::File(file_info);
+
+    for chunk in include_bytes!("sample.jpg").chunks(16 * 1024) {
+        assert_eq!(collector.write(chunk).unwrap(), chunk.len());
+    }
+
+    assert!(rx.has_changed().unwrap());
+    let latest = rx.borrow_and_update().as_bytes_per_sec();
+    assert_eq!(Some(latest), *last_speed.lock().unwrap());
+    assert!(!rx.has_changed().unwrap());
+}
+
+#[test]
+fn test_transfer_speed_follows_slowdown() {
+    let start = Instant::now();
+    let mut file_info = FileInfo::path(PathBuf::from("unused.jpg"));
+
+    // 1 MB every 100 ms for a second.
+    for i in 1..=10 {
+        file_info.update_bytes_transferred_at(1_000_000, start + Duration::from_millis(100 * i));
+    }
+    let fast = file_info.transfer_speed().as_bytes_per_sec();
+
+    // 1 kB every 500 ms for five seconds.
+    for i in 1..=10 {
+        file_info.update_bytes_transferred_at(1_000, start + Duration::from_millis(1000 + 500 * i));
+    }
+    let slow = file_info.transfer_speed().as_bytes_per_sec();
+
+    println!("Fast: {} B/s Slow: {} B/s", fast, slow);
+    assert!(fast > 5_000_000);
+    // The average since the start would still be above 1 MB/s.
+    assert!(slow < 10_000);
+}
+
+#[tokio::test]
+async fn test_resume_download_content_range() {
+    let responder = MockResponder::new(ResponderType::File);
+    let (server, tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let save_to = tempdir.path().join("downloaded_file.jpg");
+    let total = include_bytes!("sample.jpg").len() as u64;
+    fs::write(save_to.as_path(), &include_bytes!("sample.jpg")[..4500]).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::File(FileInfo::path(save_to.clone()));
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .resume_from(FileInfo::current_offset(&save_to).unwrap())
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(
+        response.content_range(),
+        Some(ContentRange::Bytes {
+            start: 4500,
+            end: total - 1,
+            total: Some(total)
+        })
+    );
+    assert_eq!(fs::read(save_to).unwrap(), include_bytes!("sample.jpg"));
+}
+
+#[test]
+fn test_content_range_unsatisfied() {
+    let response = http::Response::builder()
+        .status(StatusCode::RANGE_NOT_SATISFIABLE)
+        .header("Content-Range", "bytes */12346")
+        .body(())
+        .unwrap();
+
+    assert_eq!(
+        response.content_range(),
+        Some(ContentRange::Unsatisfied { total: 12346 })
+    );
+    assert_eq!(response.content_range().unwrap().total(), Some(12346));
+}
@@ -1,10 +1,23 @@
+use std::str::FromStr;
+
 use async_curl::CurlActor;
-use http::{Method, Request, StatusCode};
+use http::{header::CONTENT_LENGTH, Method, Request, StatusCode, Version};
+use test_case::test_case;
 use url::Url;
+use wiremock::{
+    http::HeaderName,
+    matchers::{body_bytes, header, method},
+    ResponseTemplate,
+};
 
 use crate::collector::Collector;
+use crate::error::Error;
 use crate::http_client::HttpClient;
-use crate::test::test_setup::{setup_test_environment, MockResponder, ResponderType};
+use crate::response::{ByteRange, CertInfo, ContentRange, ResponseBodyExt, ResponseExt};
+use crate::test::test_setup::{
+    setup_test_environment, setup_test_environment_with, start_chunked_server, start_raw_server,
+    MockResponder, ResponderType,
+};
 
 #[tokio::test]
 async fn test_get() {
@@ -96,3 +109,803 @@ async fn test_get_sync() {
     );
     assert!(!response.headers().is_empty());
 }
+
+#[tokio::test]
+async fn test_get_ram_capped() {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::RamCapped(Vec::new(), 4);
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let result = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await;
+
+    assert!(matches!(result, Err(Error::BodyTooLarge(4))));
+}
+
+#[tokio::test]
+async fn test_get_gzip_encoded() {
+    // "test body test body test body test body" compressed with gzip.
+    let compressed = [
+        31, 139, 8, 0, 0, 0, 0, 0, 2, 3, 43, 73, 45, 46, 81, 72, 202, 79, 169, 84, 40, 193, 199, 2,
+        0, 120, 141, 216, 231, 39, 0, 0, 0,
+    ];
+    let responder = ResponseTemplate::new(200)
+        .insert_header("Content-Encoding", "gzip")
+        .set_body_bytes(compressed.as_slice());
+    let (server, _tempdir) = setup_test_environment_with(method("GET"), responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::RamAndHeaders(Vec::new(), Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .accept_encoding("gzip")
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    let expected = "test body test body test body test body"
+        .as_bytes()
+        .to_vec();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(*response.body().as_ref().unwrap(), expected);
+    assert_eq!(
+        response.headers().get(CONTENT_LENGTH).unwrap(),
+        expected.len().to_string().as_str()
+    );
+}
+
+#[tokio::test]
+async fn test_get_chunked_content_length() {
+    let address = start_chunked_server(&[b"test ", b"body ", b"in chunks"]);
+    let target_url = format!("http://{}/test", address);
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    let expected = "test body in chunks".as_bytes().to_vec();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(*response.body().as_ref().unwrap(), expected);
+    assert_eq!(
+        response.headers().get(CONTENT_LENGTH).unwrap(),
+        expected.len().to_string().as_str()
+    );
+}
+
+#[tokio::test]
+async fn test_get_with_body() {
+    let request_body = r#"{"query":{"match_all":{}}}"#.as_bytes().to_vec();
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) =
+        setup_test_environment_with(body_bytes(request_body.clone()), responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .header("Content-Type", "application/json")
+        .body(Some(request_body))
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        *response.body().as_ref().unwrap(),
+        "test body".as_bytes().to_vec()
+    );
+}
+
+#[tokio::test]
+async fn test_get_with_body_without_content_type() {
+    let request_body = r#"{"query":{"match_all":{}}}"#.as_bytes().to_vec();
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) =
+        setup_test_environment_with(body_bytes(request_body.clone()), responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(Some(request_body))
+        .unwrap();
+
+    let response = HttpClient::new(Collector::Ram(Vec::new()))
+        .request(request)
+        .unwrap()
+        .nonblocking(CurlActor::new())
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    let requests = server.received_requests().await.unwrap();
+    // libcurl would send the body as application/x-www-form-urlencoded.
+    assert!(!requests[0]
+        .headers
+        .contains_key(&HeaderName::from_str("content-type").unwrap()));
+}
+
+#[tokio::test]
+async fn test_get_with_empty_body() {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) = setup_test_environment_with(method("GET"), responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(Some(Vec::new()))
+        .unwrap();
+
+    let response = HttpClient::new(Collector::Ram(Vec::new()))
+        .request(request)
+        .unwrap()
+        .nonblocking(CurlActor::new())
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    let requests = server.received_requests().await.unwrap();
+    // An empty body is not sent, so the GET has no Content-Length.
+    assert!(!requests[0]
+        .headers
+        .contains_key(&HeaderName::from_str("content-length").unwrap()));
+}
+
+#[tokio::test]
+async fn test_get_num_connects() {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let mut num_connects = Vec::new();
+    for _ in 0..2 {
+        let request = Request::builder()
+            .uri(target_url.as_str())
+            .method(Method::GET)
+            .body(None)
+            .unwrap();
+
+        let response = HttpClient::new(Collector::Ram(Vec::new()))
+            .request(request)
+            .unwrap()
+            .nonblocking(actor.clone())
+            .perform()
+            .await
+            .unwrap();
+
+        println!("Response: {:?}", response);
+        num_connects.push(response.transfer_info().unwrap().num_connects);
+    }
+
+    // Every transfer of the actor runs in its own multi handle, which closes its connections
+    // once the transfer has completed, so each request connects again.
+    assert_eq!(num_connects, [1, 1]);
+}
+
+#[test_case(false, 0 ; "reused connection")]
+#[test_case(true, 1 ; "no connection reuse")]
+#[tokio::test]
+async fn test_get_no_connection_reuse(no_connection_reuse: bool, expected_num_connects: u32) {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let request = || {
+        Request::builder()
+            .uri(target_url.as_str())
+            .method(Method::GET)
+            .body(None)
+            .unwrap()
+    };
+
+    let mut client = HttpClient::new(Collector::Ram(Vec::new()));
+    if no_connection_reuse {
+        client = client.no_connection_reuse().unwrap();
+    }
+    // The blocking transfers of the same handle share its connection cache.
+    let easy = client
+        .request(request())
+        .unwrap()
+        .blocking()
+        .send_request()
+        .unwrap();
+
+    let response = HttpClient::from_easy(easy)
+        .request(request())
+        .unwrap()
+        .blocking()
+        .perform()
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.transfer_info().unwrap().num_connects,
+        expected_num_connects
+    );
+}
+
+#[tokio::test]
+async fn test_get_with_query() {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) = setup_test_environment_with(
+        |request: &wiremock::Request| request.url.query() == Some("a=1&q=a+b%26c&lang=%C3%A9"),
+        responder,
+    )
+    .await;
+    let target_url = Url::parse(format!("{}/test?a=1", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .query(&[("q", "a b&c"), ("lang", "\u{e9}")])
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        *response.body().as_ref().unwrap(),
+        "test body".as_bytes().to_vec()
+    );
+}
+
+#[tokio::test]
+async fn test_get_unexpected_content_type() {
+    let responder = ResponseTemplate::new(200)
+        .set_body_bytes("<html></html>".as_bytes())
+        .insert_header("Content-Type", "text/html");
+    let (server, _tempdir) = setup_test_environment_with(method("GET"), responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let result = HttpClient::new(collector)
+        .expect_content_type("application/json")
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await;
+
+    println!("Result: {:?}", result);
+    match result {
+        Err(Error::UnexpectedContentType { expected, actual }) => {
+            assert_eq!(expected, "application/json");
+            assert_eq!(actual.as_deref(), Some("text/html"));
+        }
+        _ => panic!("the content type must be rejected"),
+    }
+}
+
+#[tokio::test]
+async fn test_get_fail_on_error() {
+    let responder = ResponseTemplate::new(404).set_body_bytes("not found".as_bytes());
+    let (server, _tempdir) = setup_test_environment_with(method("GET"), responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let result = HttpClient::new(collector)
+        .fail_on_error(true)
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await;
+
+    println!("Result: {:?}", result);
+    assert!(matches!(
+        result,
+        Err(Error::Perform(async_curl::error::Error::Curl(e))) if e.is_http_returned_error()
+    ));
+}
+
+#[tokio::test]
+async fn test_get_http_200_aliases() {
+    let address = start_raw_server(
+        b"ICY 200 OK\r\nContent-Length: 9\r\nConnection: close\r\n\r\ntest body".to_vec(),
+    );
+    let target_url = format!("http://{}/test", address);
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .http_200_aliases(&["ICY 200 OK"])
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        *response.body().as_ref().unwrap(),
+        "test body".as_bytes().to_vec()
+    );
+}
+
+#[tokio::test]
+async fn test_get_certinfo_without_tls() {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .certinfo(true)
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.transfer_info().unwrap().certificates.is_empty());
+}
+
+#[test]
+fn test_cert_info_fields() {
+    let cert = CertInfo::from(vec![
+        ("Subject".to_string(), "CN = example.com".to_string()),
+        ("Issuer".to_string(), "CN = Example CA".to_string()),
+        (
+            "Expire date".to_string(),
+            "Jan  1 00:00:00 2030 GMT".to_string(),
+        ),
+    ]);
+
+    assert_eq!(cert.subject(), Some("CN = example.com"));
+    assert_eq!(cert.issuer(), Some("CN = Example CA"));
+    assert_eq!(cert.expire_date(), Some("Jan  1 00:00:00 2030 GMT"));
+    assert_eq!(cert.start_date(), None);
+}
+
+#[tokio::test]
+async fn test_get_negotiated_http_version() {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.version(), Version::HTTP_11);
+}
+
+#[tokio::test]
+async fn test_get_http_10_server() {
+    let address =
+        start_raw_server(b"HTTP/1.0 200 OK\r\nContent-Length: 9\r\n\r\ntest body".to_vec());
+    let target_url = format!("http://{}/test", address);
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.version(), Version::HTTP_10);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_get_sockopt_function() {
+    use std::net::TcpStream;
+    use std::os::unix::io::{FromRawFd, IntoRawFd};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let called = Arc::new(AtomicBool::new(false));
+    let sockopt_called = called.clone();
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .sockopt_function(move |socket| {
+            // The socket is borrowed, so it is given back without being closed.
+            let stream = unsafe { TcpStream::from_raw_fd(socket) };
+            let result = stream.set_nodelay(true);
+            let _ = stream.into_raw_fd();
+            sockopt_called.store(true, Ordering::SeqCst);
+            result.is_ok()
+        })
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(called.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn test_get_sockopt_function_aborts() {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let result = HttpClient::new(collector)
+        .sockopt_function(|_| false)
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await;
+
+    println!("Result: {:?}", result);
+    assert!(matches!(result, Err(Error::Perform(_))));
+}
+
+#[tokio::test]
+async fn test_get_multiple_ranges() {
+    let body = b"--SEPARATOR\r\n\
+Content-Type: text/plain\r\n\
+Content-Range: bytes 0-4/20\r\n\
+\r\n\
+test \r\n\
+--SEPARATOR\r\n\
+Content-Type: text/plain\r\n\
+Content-Range: bytes 10-19/20\r\n\
+\r\n\
+in\r\nranges!\r\n\
+--SEPARATOR--\r\n";
+    let responder = ResponseTemplate::new(206)
+        .set_body_raw(body.as_slice(), "multipart/byteranges; boundary=SEPARATOR");
+    let (server, _tempdir) =
+        setup_test_environment_with(header("range", "bytes=0-4,10-"), responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .ranges(&[(0, Some(4)), (10, None)])
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(
+        response.byte_ranges().unwrap(),
+        vec![
+            ByteRange {
+                content_range: Some(ContentRange::Bytes {
+                    start: 0,
+                    end: 4,
+                    total: Some(20)
+                }),
+                data: b"test ".to_vec(),
+            },
+            ByteRange {
+                content_range: Some(ContentRange::Bytes {
+                    start: 10,
+                    end: 19,
+                    total: Some(20)
+                }),
+                data: b"in\r\nranges!".to_vec(),
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_get_multiple_ranges_ignored() {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .ranges(&[(0, Some(4)), (10, None)])
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.byte_ranges().is_none());
+}
+
+#[test]
+fn test_invalid_ranges() {
+    let result = HttpClient::new(Collector::Ram(Vec::new())).ranges(&[(10, Some(4))]);
+    assert!(matches!(result, Err(Error::Other(_))));
+
+    let result = HttpClient::new(Collector::Ram(Vec::new())).ranges(&[]);
+    assert!(matches!(result, Err(Error::Other(_))));
+}
+
+#[tokio::test]
+async fn test_get_trace_to_file() {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+    let trace_path = tempdir.path().join("trace.log");
+
+    let actor = CurlActor::new();
+    let perform = || {
+        let request = Request::builder()
+            .uri(target_url.as_str())
+            .method(Method::GET)
+            .body(None)
+            .unwrap();
+
+        HttpClient::new(Collector::Ram(Vec::new()))
+            .trace_to_file(trace_path.clone())
+            .unwrap()
+            .request(request)
+            .unwrap()
+            .nonblocking(actor.clone())
+            .perform()
+    };
+
+    // Both requests append to the same file at the same time.
+    let (first, second) = tokio::join!(perform(), perform());
+    assert_eq!(first.unwrap().status(), StatusCode::OK);
+    assert_eq!(second.unwrap().status(), StatusCode::OK);
+
+    let trace = std::fs::read_to_string(&trace_path).unwrap();
+    println!("Trace:\n{}", trace);
+    let ids = trace
+        .lines()
+        .filter(|line| line.ends_with(">> header: GET /test HTTP/1.1"))
+        .map(|line| line.split(' ').next().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(ids.len(), 2);
+    assert_ne!(ids[0], ids[1]);
+    for id in ids {
+        assert!(trace
+            .lines()
+            .any(|line| line == format!("{} << header: HTTP/1.1 200 OK", id)));
+        assert!(trace
+            .lines()
+            .any(|line| line == format!("{} << data: test body", id)));
+    }
+}
+
+/// Returns the address of a port on which nothing is listening.
+fn closed_port() -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    format!("http://{}/test", listener.local_addr().unwrap())
+}
+
+#[test]
+fn test_get_os_errno() {
+    let request = Request::builder()
+        .uri(closed_port().as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let result = HttpClient::new(Collector::Ram(Vec::new()))
+        .request(request)
+        .unwrap()
+        .blocking()
+        .perform();
+
+    match result {
+        Err(Error::Perform(async_curl::error::Error::Curl(error))) => {
+            println!("Error: {:?}", error);
+            assert!(error.is_couldnt_connect());
+            // The errno of the refused connection is not 0.
+            let extra = error.extra_description().unwrap();
+            assert!(extra.contains("(os error "));
+            assert!(!extra.contains("(os error 0)"));
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[tokio::test]
+async fn test_get_os_errno_async() {
+    let request = Request::builder()
+        .uri(closed_port().as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let result = HttpClient::new(Collector::Ram(Vec::new()))
+        .request(request)
+        .unwrap()
+        .nonblocking(CurlActor::new())
+        .perform()
+        .await;
+
+    assert!(matches!(result, Err(Error::Perform(_))));
+}
+
+#[test_case(None, Method::GET ; "request method")]
+#[test_case(Some("PURGE"), Method::from_bytes(b"PURGE").unwrap() ; "custom method")]
+#[tokio::test]
+async fn test_get_effective_method(custom_request: Option<&str>, expected: Method) {
+    // The mock server does not accept methods it does not know, like PURGE.
+    let address = start_raw_server(
+        b"HTTP/1.1 200 OK\r\nContent-Length: 9\r\nConnection: close\r\n\r\ntest body".to_vec(),
+    );
+    let target_url = format!("http://{}/test", address);
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let mut client = HttpClient::new(collector).request(request).unwrap();
+    if let Some(custom_request) = custom_request {
+        client = client.custom_request(custom_request).unwrap();
+    }
+    let response = client.nonblocking(actor).perform().await.unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.transfer_info().unwrap().method, Some(expected));
+}
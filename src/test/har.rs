@@ -0,0 +1,57 @@
+use async_curl::CurlActor;
+use http::{Method, Request, StatusCode};
+use url::Url;
+
+use crate::collector::Collector;
+use crate::har::HarRecord;
+use crate::http_client::HttpClient;
+use crate::test::test_setup::{setup_test_environment, MockResponder, ResponderType};
+
+#[tokio::test]
+async fn test_recorded() {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .header("X-Test", "recorded")
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::recorded(collector)
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let record = response.extensions().get::<HarRecord>().unwrap();
+    assert_eq!(record.method, "GET");
+    assert_eq!(record.url, target_url.as_str());
+    assert_eq!(record.status, 200);
+    assert_eq!(record.response_body, "test body".as_bytes().to_vec());
+    assert!(record
+        .request_headers
+        .iter()
+        .any(|(name, _)| name.eq_ignore_ascii_case("host")));
+    assert!(record
+        .request_headers
+        .contains(&("X-Test".to_string(), "recorded".to_string())));
+
+    #[cfg(feature = "json")]
+    {
+        let har = record.to_har_json();
+        println!("HAR: {}", har);
+        assert!(har.contains(r#""method":"GET""#));
+        assert!(har.contains(r#""status":200"#));
+    }
+}
@@ -1,12 +1,27 @@
 use std::fs;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
 use async_curl::CurlActor;
-use http::{Method, Request};
+use curl::easy::{Handler, List};
+use http::{Method, Request, Response, StatusCode};
+use test_case::test_case;
 use url::Url;
-
-use crate::collector::{Collector, ExtendedHandler, FileInfo};
+use wiremock::{
+    http::HeaderName,
+    matchers::{header, method},
+    ResponseTemplate,
+};
+
+use crate::collector::{Collector, ExtendedHandler, FileInfo, HeaderFunction, HeaderLimit};
+use crate::error::Error;
 use crate::http_client::HttpClient;
-use crate::test::test_setup::{setup_test_environment, MockResponder, ResponderType};
+use crate::response::ResponseExt;
+use crate::test::test_setup::{
+    setup_test_environment, setup_test_environment_with, start_raw_server, MockResponder,
+    ResponderType,
+};
 
 #[tokio::test]
 async fn test_with_complete_headers_ram_and_header() {
@@ -138,7 +153,7 @@ async fn test_with_complete_headers_file() {
     println!("headers: {:?}", headers);
     println!("status: {:?}", response.response_code().unwrap());
 
-    assert!(headers.is_none());
+    assert!(headers.is_some());
     assert_eq!(body, None);
     assert_eq!(response.response_code().unwrap(), 200);
     assert_eq!(fs::read(save_to).unwrap(), include_bytes!("sample.jpg"));
@@ -174,3 +189,560 @@ async fn test_with_complete_headers_ram_and_header_sync() {
     assert_eq!(body.unwrap(), "test body".as_bytes().to_vec());
     assert_eq!(response.response_code().unwrap(), 200);
 }
+
+#[test]
+fn test_request_headers() {
+    let request = Request::builder()
+        .uri("http://localhost/test")
+        .method(Method::POST)
+        .header("Content-Type", "application/json")
+        .header("X-Custom", "custom value")
+        .body(Some("{}".as_bytes().to_vec()))
+        .unwrap();
+
+    let client = HttpClient::new(Collector::Ram(Vec::new()))
+        .request(request)
+        .unwrap();
+
+    assert_eq!(
+        client.request_headers(),
+        [
+            "content-type: application/json".to_string(),
+            "x-custom: custom value".to_string()
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_header_function() {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let header_count = Arc::new(AtomicUsize::new(0));
+    let counter = header_count.clone();
+
+    let actor = CurlActor::new();
+    let collector = HeaderFunction::new(
+        Collector::RamAndHeaders(Vec::new(), Vec::new()),
+        move |header: &[u8]| {
+            println!("header: {:?}", String::from_utf8_lossy(header));
+            counter.fetch_add(1, Ordering::SeqCst);
+            true
+        },
+    );
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert!(header_count.load(Ordering::SeqCst) > 0);
+    assert!(!response.headers().is_empty());
+    assert_eq!(
+        *response.body().as_ref().unwrap(),
+        "test body".as_bytes().to_vec()
+    );
+}
+
+#[tokio::test]
+async fn test_content_disposition_filename() {
+    let responder = ResponseTemplate::new(200)
+        .insert_header(
+            "Content-Disposition",
+            "attachment; filename=\"naive.txt\"; filename*=UTF-8''na%C3%AFve.txt",
+        )
+        .set_body_bytes("test body".as_bytes());
+    let (server, _tempdir) = setup_test_environment_with(method("GET"), responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::RamAndHeaders(Vec::new(), Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(
+        response.content_disposition_filename().as_deref(),
+        Some("na\u{ef}ve.txt")
+    );
+}
+
+#[test]
+fn test_content_disposition_quoted_filename() {
+    let response = Response::builder()
+        .header(
+            "Content-Disposition",
+            "attachment; filename=\"../report; \\\"final\\\".pdf\"",
+        )
+        .body(())
+        .unwrap();
+
+    assert_eq!(
+        response.content_disposition_filename().as_deref(),
+        Some("report; \"final\".pdf")
+    );
+}
+
+#[tokio::test]
+async fn test_links() {
+    let responder = ResponseTemplate::new(200)
+        .insert_header(
+            "Link",
+            "<https://api.example.com/items?page=2>; rel=\"next\", \
+             <https://api.example.com/items?page=5>; rel=\"last\"",
+        )
+        .set_body_bytes("test body".as_bytes());
+    let (server, _tempdir) = setup_test_environment_with(method("GET"), responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::RamAndHeaders(Vec::new(), Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    let links = response.links();
+    assert_eq!(links.len(), 2);
+    assert_eq!(
+        links.get("next").map(String::as_str),
+        Some("https://api.example.com/items?page=2")
+    );
+    assert_eq!(
+        links.get("last").map(String::as_str),
+        Some("https://api.example.com/items?page=5")
+    );
+}
+
+#[tokio::test]
+async fn test_links_received_in_several_headers() {
+    let address = start_raw_server(
+        b"HTTP/1.1 200 OK\r\n\
+Link: <https://api.example.com/items?page=2>; rel=\"next\"\r\n\
+Link: <https://api.example.com/items?page=5>; rel=\"last\"\r\n\
+Content-Length: 9\r\n\r\ntest body"
+            .to_vec(),
+    );
+    let target_url = format!("http://{}/test", address);
+
+    let actor = CurlActor::new();
+    let collector = Collector::RamAndHeaders(Vec::new(), Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.headers().get_all("Link").iter().count(), 2);
+    let links = response.links();
+    assert_eq!(
+        links.get("next").map(String::as_str),
+        Some("https://api.example.com/items?page=2")
+    );
+    assert_eq!(
+        links.get("last").map(String::as_str),
+        Some("https://api.example.com/items?page=5")
+    );
+}
+
+#[test]
+fn test_links_in_several_headers() {
+    let response = Response::builder()
+        .header(
+            "Link",
+            "</items?page=1>; title=\"first, \\\"oldest\\\"\"; rel=\"prev FIRST\"",
+        )
+        .header(
+            "Link",
+            "</items?a=1,2>; rel=next, </items?page=9>; rel=\"next\"",
+        )
+        .header("Link", "invalid; rel=last")
+        .body(())
+        .unwrap();
+
+    let links = response.links();
+    assert_eq!(links.len(), 3);
+    assert_eq!(links.get("prev").map(String::as_str), Some("/items?page=1"));
+    assert_eq!(
+        links.get("first").map(String::as_str),
+        Some("/items?page=1")
+    );
+    assert_eq!(links.get("next").map(String::as_str), Some("/items?a=1,2"));
+    assert!(Response::builder().body(()).unwrap().links().is_empty());
+}
+
+#[test_case(Collector::RamAndHeaders(Vec::new(), Vec::new()), Some("HTTP/1.1 418 I'm a teapot") ; "with headers")]
+#[test_case(Collector::Ram(Vec::new()), None ; "without headers")]
+#[tokio::test]
+async fn test_status_line(collector: Collector, expected: Option<&str>) {
+    let address = start_raw_server(
+        b"HTTP/1.1 100 Continue\r\n\r\n\
+HTTP/1.1 418 I'm a teapot\r\nContent-Length: 9\r\n\r\ntest body"
+            .to_vec(),
+    );
+    let target_url = format!("http://{}/test", address);
+
+    let actor = CurlActor::new();
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::IM_A_TEAPOT);
+    assert_eq!(
+        response.transfer_info().unwrap().status_line.as_deref(),
+        expected
+    );
+}
+
+#[test_case(Collector::RamAndHeaders(Vec::new(), Vec::new()), Some("0") ; "with headers")]
+#[test_case(Collector::Ram(Vec::new()), None ; "without headers")]
+#[tokio::test]
+async fn test_trailers(collector: Collector, expected: Option<&str>) {
+    let address = start_raw_server(
+        b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nTrailer: grpc-status\r\n\r\n\
+9\r\ntest body\r\n0\r\ngrpc-status: 0\r\n\r\n"
+            .to_vec(),
+    );
+    let target_url = format!("http://{}/test", address);
+
+    let actor = CurlActor::new();
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.body().as_deref(), Some("test body".as_bytes()));
+    let trailers = &response.transfer_info().unwrap().trailers;
+    assert_eq!(
+        trailers
+            .get("grpc-status")
+            .map(|value| value.to_str().unwrap()),
+        expected
+    );
+    // The trailers are not part of the headers of the response.
+    assert!(!response.headers().contains_key("grpc-status"));
+}
+
+#[test]
+fn test_trailers_follow_final_response() {
+    let mut collector = Collector::RamAndHeaders(Vec::new(), Vec::new());
+    for line in [
+        "HTTP/1.1 100 Continue\r\n",
+        "\r\n",
+        "HTTP/1.1 200 OK\r\n",
+        "Transfer-Encoding: chunked\r\n",
+        "\r\n",
+    ] {
+        assert!(collector.header(line.as_bytes()));
+    }
+    assert_eq!(collector.trailers(), None);
+
+    assert!(collector.header(b"Grpc-Status: 0\r\n"));
+    assert!(collector.header(b"Grpc-Message: ok\r\n"));
+    assert!(collector.header(b"\r\n"));
+
+    let trailers = collector.trailers().unwrap();
+    assert_eq!(trailers.len(), 2);
+    assert_eq!(trailers.get("grpc-status").unwrap(), "0");
+    assert_eq!(trailers.get("grpc-message").unwrap(), "ok");
+
+    let (_, headers) = collector.get_response_body_and_headers();
+    let headers = headers.unwrap();
+    assert!(headers.contains_key("transfer-encoding"));
+    assert!(!headers.contains_key("grpc-status"));
+}
+
+#[test]
+fn test_ram_and_headers_with_capacity() {
+    match Collector::ram_and_headers_with_capacity(4096, 512) {
+        Collector::RamAndHeaders(body, headers) => {
+            assert!(body.is_empty());
+            assert!(body.capacity() >= 4096);
+            assert!(headers.is_empty());
+            assert!(headers.capacity() >= 512);
+        }
+        collector => panic!("unexpected collector {:?}", collector),
+    }
+}
+
+#[test]
+fn test_ram_and_headers_capped_truncate() {
+    let mut collector =
+        Collector::RamAndHeadersCapped(Vec::new(), Vec::new(), HeaderLimit::truncate(64));
+
+    assert!(collector.header(b"HTTP/1.1 200 OK\r\n"));
+    for _ in 0..100 {
+        assert!(collector.header(b"X-Padding: aaaaaaaaaaaaaaaaaaaa\r\n"));
+    }
+
+    match collector {
+        Collector::RamAndHeadersCapped(_, headers, _) => {
+            println!("Headers: {}", String::from_utf8_lossy(&headers));
+            assert!(headers.len() <= 64);
+            assert!(headers.starts_with(b"HTTP/1.1 200 OK\r\n"));
+        }
+        collector => panic!("unexpected collector {:?}", collector),
+    }
+}
+
+#[tokio::test]
+async fn test_ram_and_headers_capped_abort() {
+    let responder = ResponseTemplate::new(200)
+        .insert_header("X-Padding", "a".repeat(1024).as_str())
+        .set_body_string("test body");
+    let (server, _tempdir) = setup_test_environment_with(method("GET"), responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::RamAndHeadersCapped(Vec::new(), Vec::new(), HeaderLimit::abort(256));
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let result = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await;
+
+    println!("Result: {:?}", result);
+    assert!(matches!(result, Err(Error::Perform(_))));
+}
+
+#[tokio::test]
+async fn test_accept_language() {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) = setup_test_environment_with(
+        |request: &wiremock::Request| {
+            request
+                .headers
+                .get(&HeaderName::from_str("accept-language").unwrap())
+                .map(|value| value.to_string())
+                == Some("en-US;q=1.0, fr;q=0.8, de;q=0.125".to_string())
+        },
+        responder,
+    )
+    .await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .accept_language(&[("fr", 0.8), ("en-US", 1.0), ("de", 0.125)])
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[test_case(None ; "without accept encoding")]
+#[test_case(Some("gzip") ; "with accept encoding header")]
+#[tokio::test]
+async fn test_no_compression(accept_encoding: Option<&str>) {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) = setup_test_environment_with(
+        |request: &wiremock::Request| {
+            request
+                .headers
+                .get(&HeaderName::from_str("accept-encoding").unwrap())
+                .map(|values| {
+                    values
+                        .iter()
+                        .map(|value| value.to_string())
+                        .collect::<Vec<_>>()
+                })
+                == Some(vec!["identity".to_string()])
+        },
+        responder,
+    )
+    .await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let mut request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET);
+    if let Some(accept_encoding) = accept_encoding {
+        request = request.header("Accept-Encoding", accept_encoding);
+    }
+
+    let response = HttpClient::new(collector)
+        .accept_encoding("")
+        .unwrap()
+        .request(request.body(None).unwrap())
+        .unwrap()
+        .no_compression()
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        *response.body().as_ref().unwrap(),
+        "test body".as_bytes().to_vec()
+    );
+}
+
+#[test]
+fn test_accept_language_invalid_quality() {
+    let result = HttpClient::new(Collector::Ram(Vec::new())).accept_language(&[("en", 1.5)]);
+
+    assert!(matches!(result, Err(Error::Other(_))));
+}
+
+#[tokio::test]
+async fn test_before_perform() {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) =
+        setup_test_environment_with(header("x-trace-id", "1234"), responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .before_perform(|easy| {
+            let mut headers = List::new();
+            headers.append("X-Trace-Id: 1234").unwrap();
+            easy.http_headers(headers).unwrap();
+        })
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[test_case(&["127.0.0.1\tFALSE\t/\tFALSE\t0\tsession\tabc"], Some("session=abc") ; "netscape line")]
+#[test_case(&["Set-Cookie: session=abc"], Some("session=abc") ; "set-cookie line")]
+#[test_case(&["127.0.0.1\tFALSE\t/\tFALSE\t0\tsession\tabc", "SESS"], None ; "session cookies erased")]
+#[test_case(&["127.0.0.1\tFALSE\t/\tFALSE\t0\tsession\tabc", "RELOAD"], Some("session=abc") ; "reload without cookie file")]
+#[tokio::test]
+async fn test_add_cookie(cookies: &[&str], expected: Option<&'static str>) {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) = setup_test_environment_with(
+        move |request: &wiremock::Request| {
+            request
+                .headers
+                .get(&HeaderName::from_str("cookie").unwrap())
+                .map(|value| value.to_string())
+                == expected.map(|cookie| cookie.to_string())
+        },
+        responder,
+    )
+    .await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let mut client = HttpClient::new(collector);
+    for cookie in cookies {
+        client = client.add_cookie(cookie).unwrap();
+    }
+    let response = client
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+}
@@ -0,0 +1,102 @@
+use async_curl::CurlActor;
+use futures::StreamExt;
+use http::{Method, Request, StatusCode};
+use serde_json::{json, Value};
+use test_case::test_case;
+use url::Url;
+use wiremock::{matchers::method, ResponseTemplate};
+
+use crate::collector::StreamingCollector;
+use crate::error::Error;
+use crate::http_client::HttpClient;
+use crate::ndjson::{NdJsonDecoder, NdJsonStream};
+use crate::test::test_setup::setup_test_environment_with;
+
+const NDJSON_STREAM: &str = "{\"id\":1,\"message\":\"started\"}\n\
+    \n\
+    {\"id\":2,\"message\":\"caf\u{e9}\",\"tags\":[\"a\",\"b\"]}\r\n\
+    \x20\x20\n\
+    {\"id\":3,\"message\":\"done\"}";
+
+fn expected_values() -> Vec<Value> {
+    vec![
+        json!({"id": 1, "message": "started"}),
+        json!({"id": 2, "message": "caf\u{e9}", "tags": ["a", "b"]}),
+        json!({"id": 3, "message": "done"}),
+    ]
+}
+
+#[test_case(1 ; "one byte chunks")]
+#[test_case(3 ; "three byte chunks")]
+#[test_case(17 ; "seventeen byte chunks")]
+#[test_case(1024 ; "single chunk")]
+fn test_ndjson_decoder(chunk_size: usize) {
+    let mut decoder = NdJsonDecoder::<Value>::new();
+    let mut values = Vec::new();
+    // Lines and the multi-byte character are split across the chunks.
+    for chunk in NDJSON_STREAM.as_bytes().chunks(chunk_size) {
+        values.extend(decoder.decode(chunk).into_iter().map(Result::unwrap));
+    }
+    // The last line does not end with a newline.
+    values.extend(decoder.finish().map(Result::unwrap));
+
+    assert_eq!(values, expected_values());
+    assert!(decoder.finish().is_none());
+}
+
+#[tokio::test]
+async fn test_ndjson_stream() {
+    let responder = ResponseTemplate::new(200)
+        .set_body_bytes(NDJSON_STREAM.as_bytes())
+        .insert_header("Content-Type", "application/x-ndjson");
+    let (server, _tempdir) = setup_test_environment_with(method("GET"), responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let chunks = HttpClient::new(StreamingCollector::new())
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .into_chunk_stream();
+    let mut stream = NdJsonStream::<Value>::new(chunks);
+
+    let mut values = Vec::new();
+    while let Some(value) = stream.next().await {
+        values.push(value.unwrap());
+    }
+
+    assert_eq!(stream.response().unwrap().status(), StatusCode::OK);
+    assert_eq!(values, expected_values());
+}
+
+#[tokio::test]
+async fn test_ndjson_stream_invalid_line() {
+    let responder = ResponseTemplate::new(200).set_body_bytes("{\"id\":1}\nnot json\n{\"id\":2}\n");
+    let (server, _tempdir) = setup_test_environment_with(method("GET"), responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let chunks = HttpClient::new(StreamingCollector::new())
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .into_chunk_stream();
+    let results: Vec<_> = NdJsonStream::<Value>::new(chunks).collect().await;
+
+    assert_eq!(results.len(), 3);
+    assert_eq!(*results[0].as_ref().unwrap(), json!({"id": 1}));
+    assert!(matches!(results[1], Err(Error::Other(_))));
+    assert_eq!(*results[2].as_ref().unwrap(), json!({"id": 2}));
+}
@@ -0,0 +1,237 @@
+use std::time::{Duration, Instant};
+
+use test_case::test_case;
+
+use crate::collector::Collector;
+use crate::error::Error;
+use crate::http_client::{Bps, BytesOffset, FileSize, HttpClient, Mbps};
+
+const TEST_CA: &str = r#"-----BEGIN CERTIFICATE-----
+MIIBnjCCAUOgAwIBAgIUOlfSHFH42HMe0lFwHAZP9fsc4FkwCgYIKoZIzj0EAwIw
+IzEhMB8GA1UEAwwYY3VybC1odHRwLWNsaWVudCB0ZXN0IENBMCAXDTI2MTAxNjA5
+NTcyMloYDzIxMjYwOTIyMDk1NzIyWjAjMSEwHwYDVQQDDBhjdXJsLWh0dHAtY2xp
+ZW50IHRlc3QgQ0EwWTATBgcqhkjOPQIBBggqhkjOPQMBBwNCAAQ5FkC6nZgybeEC
+mKrKJazNDkSqQSxrh2NKiVwZ/d4Fzd2DsONFtLYmUbNtGS/74u1vpN/e2k3oQJyY
+aYtHlLIMo1MwUTAdBgNVHQ4EFgQU438eI78BBgoMU4b28m/uo5L6Oj8wHwYDVR0j
+BBgwFoAU438eI78BBgoMU4b28m/uo5L6Oj8wDwYDVR0TAQH/BAUwAwEB/zAKBggq
+hkjOPQQDAgNJADBGAiEAl9T9FqiJdPyCoWHPFg43XI3UboT7w1HE8rMtlrJxaO0C
+IQCQBoLod9mPw4Rm4qtseQ/UKDQanttzMUd/yMadIVB9fA==
+-----END CERTIFICATE-----
+"#;
+
+#[test]
+fn test_nosignal() {
+    let client = HttpClient::new(Collector::Ram(Vec::new()))
+        .nosignal(true)
+        .unwrap()
+        .nosignal(false);
+
+    assert!(client.is_ok());
+}
+
+#[test]
+fn test_deadline_passed() {
+    let result = HttpClient::new(Collector::Ram(Vec::new()))
+        .url("http://localhost/test")
+        .unwrap()
+        .deadline(Instant::now() - Duration::from_secs(1))
+        .unwrap()
+        .blocking()
+        .perform();
+
+    assert!(matches!(result, Err(Error::Other(_))));
+}
+
+#[test]
+fn test_cainfo_blob() {
+    let client = HttpClient::new(Collector::Ram(Vec::new())).cainfo_blob(TEST_CA.as_bytes());
+
+    assert!(client.is_ok());
+}
+
+#[test_case("PEM", "PEM" ; "pem")]
+#[test_case("DER", "DER" ; "der")]
+#[test_case("P12", "PEM" ; "p12")]
+#[test_case("PEM", "ENG" ; "engine key")]
+fn test_sslcert_and_sslkey_type(cert_type: &str, key_type: &str) {
+    let client = HttpClient::new(Collector::Ram(Vec::new()))
+        .sslcert("client.crt")
+        .unwrap()
+        .sslcert_type(cert_type)
+        .unwrap()
+        .sslkey("client.key")
+        .unwrap()
+        .sslkey_type(key_type);
+
+    assert!(client.is_ok());
+}
+
+#[test]
+fn test_async_dns_available() {
+    let available = HttpClient::<Collector>::async_dns_available();
+
+    println!("Asynchronous DNS available: {}", available);
+}
+
+#[test]
+fn test_timeout_secs() {
+    let client = HttpClient::new(Collector::Ram(Vec::new())).timeout_secs(2 * 60 * 60);
+    assert!(client.is_ok());
+
+    let client = HttpClient::new(Collector::Ram(Vec::new())).timeout_secs(u64::MAX);
+    assert!(matches!(client, Err(Error::Other(_))));
+}
+
+#[test]
+fn test_ssl_cipher_list() {
+    let client = HttpClient::new(Collector::Ram(Vec::new()))
+        .ssl_cipher_list("ECDHE-RSA-AES128-GCM-SHA256:ECDHE-ECDSA-AES128-GCM-SHA256");
+
+    assert!(client.is_ok());
+}
+
+#[test_case(true ; "enabled")]
+#[test_case(false ; "disabled")]
+fn test_ssl_sessionid_cache(enable: bool) {
+    let client = HttpClient::new(Collector::Ram(Vec::new())).ssl_sessionid_cache(enable);
+
+    assert!(client.is_ok());
+}
+
+#[test]
+fn test_tls13_ciphers() {
+    let client = HttpClient::new(Collector::Ram(Vec::new()))
+        .tls13_ciphers("TLS_AES_256_GCM_SHA384:TLS_CHACHA20_POLY1305_SHA256");
+
+    assert!(client.is_ok());
+}
+
+#[test]
+fn test_unsupported_scheme() {
+    let result = HttpClient::new(Collector::Ram(Vec::new())).url("unknown://localhost/test");
+
+    match result {
+        Err(Error::Other(message)) => assert_eq!(message, "unsupported scheme: unknown"),
+        _ => panic!("the unsupported scheme must be rejected"),
+    }
+}
+
+#[test]
+fn test_timeout_and_connect() {
+    let client = HttpClient::new(Collector::Ram(Vec::new()))
+        .timeout_and_connect(Duration::from_secs(30), Duration::from_secs(5));
+
+    assert!(client.is_ok());
+}
+
+#[test]
+fn test_timeout_and_connect_longer_connect() {
+    let result = HttpClient::new(Collector::Ram(Vec::new()))
+        .timeout_and_connect(Duration::from_secs(5), Duration::from_secs(30));
+
+    assert!(matches!(result, Err(Error::Other(_))));
+}
+
+#[test]
+fn test_dns_servers() {
+    match HttpClient::new(Collector::Ram(Vec::new())).dns_servers("8.8.8.8,8.8.4.4") {
+        Ok(_) => {}
+        // libcurl was built without c-ares.
+        Err(Error::Curl(e)) => println!("DNS servers are not supported: {}", e),
+        Err(e) => panic!("unexpected error {:?}", e),
+    }
+}
+
+#[test]
+fn test_doh_url() {
+    match HttpClient::new(Collector::Ram(Vec::new()))
+        .doh_url("https://dns.example/dns-query")
+        .and_then(|client| client.doh_ssl_verify_peer(false))
+        .and_then(|client| client.doh_ssl_verify_host(false))
+    {
+        Ok(_) => {}
+        // libcurl is older than 7.62.0 or was built without DoH.
+        Err(Error::Curl(e)) => println!("DNS-over-HTTPS is not supported: {}", e),
+        Err(e) => panic!("unexpected error {:?}", e),
+    }
+}
+
+#[test]
+fn test_mbps_to_bps() {
+    assert_eq!(*Bps::from(Mbps::from(8)), 1_000_000);
+    assert_eq!(*Bps::from(Mbps::from(100_000)), 12_500_000_000);
+}
+
+#[test]
+fn test_speed_mbps() {
+    let client = HttpClient::new(Collector::Ram(Vec::new()))
+        .download_speed_mbps(Mbps::from(8))
+        .unwrap()
+        .upload_speed_mbps(Mbps::from(8));
+
+    assert!(client.is_ok());
+}
+
+#[test]
+fn test_large_offset_and_file_size() {
+    let large = u32::MAX as u64 + 1024;
+
+    assert_eq!(*BytesOffset::from(large), large);
+    assert_eq!(*FileSize::from(large), large);
+    assert_eq!(*BytesOffset::from(4500usize), 4500);
+
+    let client = HttpClient::new(Collector::Ram(Vec::new()))
+        .resume_from(BytesOffset::from(large))
+        .unwrap()
+        .upload_file_size(FileSize::from(large));
+
+    assert!(client.is_ok());
+}
+
+#[test]
+fn test_buffer_size() {
+    let client = HttpClient::new(Collector::Ram(Vec::new()))
+        .download_buffer_size(64 * 1024)
+        .unwrap()
+        .buffer_size(1024)
+        .unwrap()
+        .upload_buffer_size(128 * 1024);
+
+    assert!(client.is_ok());
+}
+
+#[test]
+fn test_buffer_size_out_of_range() {
+    let too_small = HttpClient::new(Collector::Ram(Vec::new())).download_buffer_size(512);
+    assert!(matches!(too_small, Err(Error::Other(_))));
+
+    let too_large = HttpClient::new(Collector::Ram(Vec::new())).buffer_size(64 * 1024 * 1024);
+    assert!(matches!(too_large, Err(Error::Other(_))));
+
+    let too_large_upload =
+        HttpClient::new(Collector::Ram(Vec::new())).upload_buffer_size(4 * 1024 * 1024);
+    assert!(matches!(too_large_upload, Err(Error::Other(_))));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_abstract_unix_socket() {
+    let client = HttpClient::new(Collector::Ram(Vec::new()))
+        .url("http://localhost/test")
+        .unwrap()
+        .abstract_unix_socket("curl-http-client-test");
+
+    assert!(client.is_ok());
+
+    let invalid = HttpClient::new(Collector::Ram(Vec::new())).abstract_unix_socket("bad\0name");
+    assert!(matches!(invalid, Err(Error::Curl(_))));
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+#[test_case(true ; "enabled")]
+#[test_case(false ; "disabled")]
+fn test_tcp_fastopen(enable: bool) {
+    let client = HttpClient::new(Collector::Ram(Vec::new())).tcp_fastopen(enable);
+
+    assert!(client.is_ok());
+}
@@ -1,10 +1,19 @@
+use std::fs;
+use std::str::FromStr;
+
 use async_curl::CurlActor;
 use http::{Method, Request, StatusCode};
+use test_case::test_case;
 use url::Url;
+use wiremock::{http::HeaderName, matchers::header};
 
-use crate::collector::Collector;
-use crate::http_client::HttpClient;
-use crate::test::test_setup::{setup_test_environment, MockResponder, ResponderType};
+use crate::collector::{Collector, FileInfo};
+use crate::error::Error;
+use crate::http_client::{Body, HttpClient};
+use crate::mime::MimeForm;
+use crate::test::test_setup::{
+    setup_test_environment, setup_test_environment_with, MockResponder, ResponderType,
+};
 
 #[tokio::test]
 async fn test_post() {
@@ -256,3 +265,251 @@ async fn test_post_async_not_option_empty_string() {
     assert_eq!(*response.body(), None);
     assert!(!response.headers().is_empty());
 }
+
+#[tokio::test]
+async fn test_post_file() {
+    let responder = MockResponder::new(ResponderType::File);
+    let (server, tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let to_be_posted = tempdir.path().join("file_to_be_posted.jpg");
+    fs::write(to_be_posted.as_path(), include_bytes!("sample.jpg")).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::File(FileInfo::path(to_be_posted));
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::POST)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(*response.body(), None);
+}
+
+#[tokio::test]
+async fn test_post_string() {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::POST)
+        .body(String::from("test body"))
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(*response.body(), None);
+}
+
+#[tokio::test]
+async fn test_post_str_sync() {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::POST)
+        .body("test body")
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .blocking()
+        .perform()
+        .unwrap();
+
+    println!("Response: {:?}", response);
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(*response.body(), None);
+}
+
+#[test]
+fn test_post_body_above_max_inline_body() {
+    let request = Request::builder()
+        .uri("http://127.0.0.1/test")
+        .method(Method::POST)
+        .body(Some("test body".as_bytes().to_vec()))
+        .unwrap();
+
+    let result = HttpClient::new(Collector::Ram(Vec::new()))
+        .max_inline_body(8)
+        .request(request);
+
+    assert!(matches!(result, Err(Error::Other(_))));
+}
+
+#[tokio::test]
+async fn test_post_body_at_max_inline_body() {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::POST)
+        .body(Some("test body".as_bytes().to_vec()))
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .max_inline_body(9)
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_post_explicit_empty_body() {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) =
+        setup_test_environment_with(header("content-length", "0"), responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::POST)
+        .body(Body::Empty)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        *response.body().as_ref().unwrap(),
+        "test body".as_bytes().to_vec()
+    );
+}
+
+#[test_case(Body::from(Vec::new()), Body::Bytes(Vec::new()) ; "empty vec")]
+#[test_case(Body::from("test body"), Body::Bytes(b"test body".to_vec()) ; "str")]
+#[test_case(Body::from(String::from("test body")), Body::Bytes(b"test body".to_vec()) ; "string")]
+#[test_case(Body::from(&b"test body"[..]), Body::Bytes(b"test body".to_vec()) ; "slice")]
+#[test_case(Body::from(Some(b"test body".to_vec())), Body::Bytes(b"test body".to_vec()) ; "some")]
+#[test_case(Body::from(None), Body::Stream ; "none")]
+fn test_body_from(body: Body, expected: Body) {
+    assert_eq!(body, expected);
+}
+
+#[tokio::test]
+async fn test_post_mime_form() {
+    fn contains(body: &[u8], needle: &[u8]) -> bool {
+        body.windows(needle.len()).any(|window| window == needle)
+    }
+
+    let large_file_size = 2 * 1024 * 1024;
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, tempdir) = setup_test_environment_with(
+        move |request: &wiremock::Request| {
+            let body = request.body.as_slice();
+            request
+                .headers
+                .get(&HeaderName::from_str("content-type").unwrap())
+                .map(|value| {
+                    value
+                        .to_string()
+                        .starts_with("multipart/form-data; boundary=")
+                })
+                == Some(true)
+                && contains(body, b"name=\"title\"\r\n\r\nreport\r\n")
+                && contains(
+                    body,
+                    b"name=\"attachment\"; filename=\"upload.txt\"\r\n\
+Content-Type: text/plain\r\n\r\nfile contents\r\n",
+                )
+                && contains(
+                    body,
+                    b"name=\"avatar\"; filename=\"avatar.png\"\r\n\
+Content-Type: image/png\r\n\r\n\x89PNG\r\n",
+                )
+                && contains(body, b"name=\"large\"; filename=\"large.bin\"")
+                && body.len() > large_file_size
+        },
+        responder,
+    )
+    .await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let upload_path = tempdir.path().join("upload.txt");
+    fs::write(&upload_path, "file contents").unwrap();
+    // The large file is streamed from disk while the request is sent.
+    let large_path = tempdir.path().join("large.bin");
+    fs::write(&large_path, vec![b'a'; large_file_size]).unwrap();
+
+    let form = MimeForm::new()
+        .text("title", "report")
+        .file("attachment", &upload_path)
+        .data("avatar", b"\x89PNG".to_vec(), "avatar.png", "image/png")
+        .file("large", &large_path);
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::POST)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .mime_form(form)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[test]
+fn test_post_mime_form_missing_file() {
+    let form = MimeForm::new().file("attachment", "./no-such-file.txt");
+    let result = HttpClient::new(Collector::Ram(Vec::new())).mime_form(form);
+
+    assert!(matches!(result, Err(Error::Other(_))));
+}
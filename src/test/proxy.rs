@@ -0,0 +1,105 @@
+use async_curl::CurlActor;
+use http::{Method, Request, StatusCode};
+use url::Url;
+
+use crate::collector::Collector;
+use crate::error::Error;
+use crate::http_client::HttpClient;
+use crate::test::test_setup::{
+    setup_test_environment, start_connect_proxy, MockResponder, ResponderType,
+};
+
+#[tokio::test]
+async fn test_suppress_connect_headers() {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+    let proxy = start_connect_proxy();
+
+    let actor = CurlActor::new();
+    for suppress in [false, true] {
+        let collector = Collector::RamAndHeaders(Vec::new(), Vec::new());
+        let request = Request::builder()
+            .uri(target_url.as_str())
+            .method(Method::GET)
+            .body(None)
+            .unwrap();
+
+        let response = HttpClient::new(collector)
+            .proxy(format!("http://{}", proxy).as_str())
+            .unwrap()
+            .http_proxy_tunnel(true)
+            .unwrap()
+            .suppress_connect_headers(suppress)
+            .unwrap()
+            .request(request)
+            .unwrap()
+            .nonblocking(actor.clone())
+            .perform()
+            .await
+            .unwrap();
+
+        println!("Response: {:?}", response);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            *response.body().as_ref().unwrap(),
+            "test body".as_bytes().to_vec()
+        );
+        assert_eq!(response.headers().contains_key("x-proxy"), !suppress);
+    }
+}
+
+#[test]
+fn test_proxy_ssl_verify() {
+    let client = HttpClient::new(Collector::Ram(Vec::new()))
+        .proxy_ssl_verify_peer(false)
+        .and_then(|client| client.proxy_ssl_verify_host(false));
+
+    assert!(client.is_ok());
+}
+
+#[tokio::test]
+async fn test_clear_proxy() {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) = setup_test_environment(responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+    let proxy = format!("http://{}", start_connect_proxy());
+
+    // The options that are shared by all requests route them through the proxy.
+    let configure =
+        |client: HttpClient<Collector>| -> Result<HttpClient<Collector>, Error<Collector>> {
+            client.proxy(proxy.as_str())?.http_proxy_tunnel(true)
+        };
+
+    let actor = CurlActor::new();
+    for (use_proxy, clear_proxy) in [(true, false), (true, true), (false, false)] {
+        let request = Request::builder()
+            .uri(target_url.as_str())
+            .method(Method::GET)
+            .body(None)
+            .unwrap();
+
+        let mut client = HttpClient::new(Collector::RamAndHeaders(Vec::new(), Vec::new()));
+        if use_proxy {
+            client = configure(client).unwrap();
+        }
+        if clear_proxy {
+            client = client.clear_proxy().unwrap();
+        }
+        let response = client
+            .request(request)
+            .unwrap()
+            .nonblocking(actor.clone())
+            .perform()
+            .await
+            .unwrap();
+
+        println!("Response: {:?}", response);
+        assert_eq!(response.status(), StatusCode::OK);
+        // Only the CONNECT response of the proxy has this header.
+        assert_eq!(
+            response.headers().contains_key("x-proxy"),
+            use_proxy && !clear_proxy
+        );
+    }
+}
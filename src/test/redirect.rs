@@ -0,0 +1,261 @@
+use std::str::FromStr;
+
+use async_curl::CurlActor;
+use http::{Method, Request, StatusCode};
+use test_case::test_case;
+use url::Url;
+use wiremock::{
+    http::HeaderName,
+    matchers::{method, path},
+    Mock, ResponseTemplate,
+};
+
+use crate::collector::{Collector, FileInfo};
+use crate::error::Error;
+use crate::http_client::HttpClient;
+use crate::test::test_setup::{
+    setup_test_environment, setup_test_environment_with, MockResponder, ResponderType,
+};
+
+#[tokio::test]
+async fn test_redirect_without_location() {
+    let responder = ResponseTemplate::new(302).set_body_bytes("moved".as_bytes());
+    let (server, _tempdir) = setup_test_environment_with(method("GET"), responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let result = HttpClient::new(collector)
+        .follow_location(true)
+        .unwrap()
+        .reject_redirect_without_location(true)
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await;
+
+    println!("Result: {:?}", result);
+    assert!(matches!(result, Err(Error::Other(_))));
+}
+
+#[tokio::test]
+async fn test_follow_same_origin_relative_location() {
+    let responder = ResponseTemplate::new(302).insert_header("Location", "final");
+    let (server, _tempdir) = setup_test_environment_with(method("GET"), responder).await;
+    Mock::given(path("/final"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes("test body".as_bytes()))
+        .mount(&server)
+        .await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .follow_same_origin(5)
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        *response.body().as_ref().unwrap(),
+        "test body".as_bytes().to_vec()
+    );
+}
+
+#[tokio::test]
+async fn test_follow_same_origin_refuses_other_origin() {
+    let other_responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (other_server, _other_tempdir) = setup_test_environment(other_responder).await;
+
+    let responder = ResponseTemplate::new(302)
+        .insert_header("Location", format!("{}/test", other_server.uri()).as_str());
+    let (server, _tempdir) = setup_test_environment_with(method("GET"), responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let result = HttpClient::new(collector)
+        .follow_same_origin(5)
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await;
+
+    println!("Result: {:?}", result);
+    assert!(matches!(result, Err(Error::Other(_))));
+    assert!(other_server.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_follow_same_origin_max_redirections() {
+    let responder = ResponseTemplate::new(301).insert_header("Location", "/test");
+    let (server, _tempdir) = setup_test_environment_with(method("GET"), responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let result = HttpClient::new(collector)
+        .follow_same_origin(2)
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .blocking()
+        .perform();
+
+    println!("Result: {:?}", result);
+    assert!(matches!(result, Err(Error::Other(_))));
+}
+
+#[test_case(false ; "stripped by default")]
+#[test_case(true ; "kept when unrestricted")]
+#[tokio::test]
+async fn test_unrestricted_auth_cross_host_redirect(unrestricted: bool) {
+    let other_responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (other_server, _other_tempdir) = setup_test_environment(other_responder).await;
+    // The other server is reached through another host name, localhost instead of 127.0.0.1.
+    let other_url = format!("http://localhost:{}/test", other_server.address().port());
+
+    let responder = ResponseTemplate::new(302).insert_header("Location", other_url.as_str());
+    let (server, _tempdir) = setup_test_environment_with(method("GET"), responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .header("Authorization", "Bearer secret-token")
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .follow_location(true)
+        .unwrap()
+        .unrestricted_auth(unrestricted)
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let authorization = HeaderName::from_str("authorization").unwrap();
+    let first = server.received_requests().await.unwrap();
+    assert!(first[0].headers.contains_key(&authorization));
+
+    let redirected = other_server.received_requests().await.unwrap();
+    assert_eq!(redirected.len(), 1);
+    assert_eq!(
+        redirected[0].headers.contains_key(&authorization),
+        unrestricted
+    );
+}
+
+async fn start_redirect_with_body() -> wiremock::MockServer {
+    let responder = ResponseTemplate::new(302)
+        .insert_header("Location", "/final")
+        .insert_header("X-Redirect", "yes")
+        .set_body_bytes("this resource has moved".as_bytes());
+    let (server, _tempdir) = setup_test_environment_with(method("GET"), responder).await;
+    Mock::given(path("/final"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes("test body".as_bytes()))
+        .mount(&server)
+        .await;
+    server
+}
+
+#[test_case(Collector::Ram(Vec::new()) ; "ram")]
+#[test_case(Collector::RamAndHeaders(Vec::new(), Vec::new()) ; "ram and headers")]
+#[tokio::test]
+async fn test_follow_same_origin_drops_redirect_body(collector: Collector) {
+    let server = start_redirect_with_body().await;
+    let target_url = format!("{}/test", server.uri());
+
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .follow_same_origin(5)
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(CurlActor::new())
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.body().as_deref(), Some("test body".as_bytes()));
+    assert!(!response.headers().contains_key("x-redirect"));
+}
+
+#[tokio::test]
+async fn test_follow_same_origin_drops_redirect_body_from_file() {
+    let server = start_redirect_with_body().await;
+    let target_url = format!("{}/test", server.uri());
+    let tempdir = tempfile::TempDir::with_prefix_in("test", "./").unwrap();
+    let save_to = tempdir.path().join("downloaded_file.txt");
+    // The download is appended to what the file already holds.
+    std::fs::write(&save_to, "existing ").unwrap();
+
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(Collector::File(FileInfo::path(save_to.clone())))
+        .follow_same_origin(5)
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(CurlActor::new())
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(std::fs::read(save_to).unwrap(), b"existing test body");
+}
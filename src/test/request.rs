@@ -0,0 +1,127 @@
+#[cfg(feature = "json")]
+use std::io::Read;
+use std::str::FromStr;
+
+use async_curl::CurlActor;
+#[cfg(feature = "json")]
+use flate2::read::GzDecoder;
+use http::{Method, StatusCode};
+#[cfg(feature = "json")]
+use serde_json::{json, Value};
+use wiremock::http::HeaderName;
+
+use crate::collector::Collector;
+use crate::http_client::HttpClient;
+use crate::request::request_builder;
+use crate::test::test_setup::{setup_test_environment_with, MockResponder, ResponderType};
+
+fn has_header(request: &wiremock::Request, name: &str, value: &str) -> bool {
+    request
+        .headers
+        .get(&HeaderName::from_str(name).unwrap())
+        .map(|header| header.to_string())
+        == Some(value.to_string())
+}
+
+#[tokio::test]
+async fn test_request_builder_headers() {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) = setup_test_environment_with(
+        |request: &wiremock::Request| {
+            has_header(request, "accept", "application/json")
+                && has_header(request, "x-api-key", "secret")
+                && has_header(request, "x-trace-id", "42")
+        },
+        responder,
+    )
+    .await;
+    let target_url = format!("{}/test", server.uri());
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = request_builder(Method::GET, target_url.as_str())
+        .headers(&[("Accept", "application/json"), ("X-Api-Key", "secret")])
+        .header("X-Trace-Id", "42")
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        *response.body().as_ref().unwrap(),
+        "test body".as_bytes().to_vec()
+    );
+}
+
+#[test]
+fn test_request_builder_invalid_header() {
+    let result = request_builder(Method::GET, "http://127.0.0.1/test")
+        .headers(&[("Invalid Name", "value")])
+        .body(None);
+
+    assert!(result.is_err());
+}
+
+#[cfg(feature = "json")]
+#[tokio::test]
+async fn test_request_builder_json_gzip() {
+    let payload = json!({
+        "name": "curl-http-client",
+        "tags": ["http", "curl"],
+        "downloads": 1234,
+    });
+    let expected = payload.clone();
+
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) = setup_test_environment_with(
+        move |request: &wiremock::Request| {
+            let header_count = |name: &str| {
+                request
+                    .headers
+                    .get(&HeaderName::from_str(name).unwrap())
+                    .map(|values| values.iter().count())
+                    .unwrap_or_default()
+            };
+            let mut json = String::new();
+            let decompressed = GzDecoder::new(request.body.as_slice())
+                .read_to_string(&mut json)
+                .is_ok();
+
+            decompressed
+                && has_header(request, "content-type", "application/json")
+                && has_header(request, "content-encoding", "gzip")
+                && header_count("content-type") == 1
+                && header_count("content-encoding") == 1
+                && serde_json::from_str::<Value>(&json).ok().as_ref() == Some(&expected)
+        },
+        responder,
+    )
+    .await;
+    let target_url = format!("{}/test", server.uri());
+
+    let actor = CurlActor::new();
+    let collector = Collector::Ram(Vec::new());
+    let request = request_builder(Method::POST, target_url.as_str())
+        .header("Content-Type", "text/plain")
+        .json_gzip(&payload)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+}
@@ -0,0 +1,89 @@
+use async_curl::CurlActor;
+use http::{Method, Request, StatusCode};
+use wiremock::{matchers::method, ResponseTemplate};
+
+use crate::collector::Collector;
+use crate::http_client::HttpClient;
+use crate::resume::{resume_support, ResumeSupport};
+use crate::test::test_setup::{
+    setup_test_environment, setup_test_environment_with, MockResponder, ResponderType,
+};
+
+#[tokio::test]
+async fn test_head_request() {
+    let responder = MockResponder::new(ResponderType::File);
+    let (server, _tempdir) = setup_test_environment(responder).await;
+    let target_url = format!("{}/test", server.uri());
+
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::HEAD)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(Collector::Ram(Vec::new()))
+        .request(request)
+        .unwrap()
+        .nonblocking(CurlActor::new())
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.body(), &None);
+}
+
+#[tokio::test]
+async fn test_resume_support_without_accept_ranges() {
+    // The mock honors ranges but does not announce them in the response to HEAD.
+    let responder = MockResponder::new(ResponderType::File);
+    let (server, _tempdir) = setup_test_environment(responder).await;
+    let target_url = format!("{}/test", server.uri());
+
+    let support = resume_support(CurlActor::new(), target_url.as_str())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        support,
+        ResumeSupport {
+            resumable: true,
+            total_size: Some(include_bytes!("sample.jpg").len() as u64),
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_resume_support_with_accept_ranges() {
+    // Only HEAD is answered, so the support must come from the Accept-Ranges header.
+    let responder =
+        ResponseTemplate::new(StatusCode::OK.as_u16()).insert_header("Accept-Ranges", "bytes");
+    let (server, _tempdir) = setup_test_environment_with(method("HEAD"), responder).await;
+    let target_url = format!("{}/test", server.uri());
+
+    let support = resume_support(CurlActor::new(), target_url.as_str())
+        .await
+        .unwrap();
+
+    assert!(support.resumable);
+}
+
+#[tokio::test]
+async fn test_resume_support_ranges_ignored() {
+    let responder = MockResponder::new(ResponderType::Body(b"test body".to_vec()));
+    let (server, _tempdir) = setup_test_environment(responder).await;
+    let target_url = format!("{}/test", server.uri());
+
+    let support = resume_support(CurlActor::new(), target_url.as_str())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        support,
+        ResumeSupport {
+            resumable: false,
+            total_size: Some(9),
+        }
+    );
+}
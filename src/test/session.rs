@@ -0,0 +1,254 @@
+use std::time::Duration;
+
+use async_curl::CurlActor;
+use http::{Method, Request, StatusCode};
+use test_case::test_case;
+use wiremock::{
+    matchers::{header, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use crate::collector::Collector;
+use crate::http_client::HttpClient;
+use crate::response::ResponseExt;
+use crate::session::Session;
+use crate::test::test_setup::{
+    setup_test_environment, setup_test_environment_with, MockResponder, ResponderType,
+};
+
+#[tokio::test]
+async fn test_session_sequential_requests() {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) = setup_test_environment(responder).await;
+    let target_url = format!("{}/test", server.uri());
+
+    let session = Session::new(CurlActor::new())
+        .with_options(|client| client.connect_timeout(Duration::from_secs(5)));
+
+    for i in 0..3 {
+        let response = session.get(target_url.as_str()).await.unwrap();
+
+        println!("Response: {:?}", response);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            *response.body().as_ref().unwrap(),
+            "test body".as_bytes().to_vec()
+        );
+        assert!(!response.headers().is_empty());
+        // The handle of the previous request is reused.
+        assert_eq!(session.idle_handles(), 1);
+        // The connection of the first request is reused as well.
+        let num_connects = response.transfer_info().unwrap().num_connects;
+        assert_eq!(num_connects, if i == 0 { 1 } else { 0 });
+    }
+}
+
+#[test_case(Method::DELETE ; "delete")]
+#[test_case(Method::PATCH ; "patch")]
+#[test_case(Method::OPTIONS ; "options")]
+#[tokio::test]
+async fn test_session_other_methods(request_method: Method) {
+    let server = MockServer::start().await;
+    Mock::given(method(request_method.as_str()))
+        .and(path("/items/1"))
+        .respond_with(ResponseTemplate::new(204))
+        .mount(&server)
+        .await;
+
+    let body = (request_method == Method::PATCH).then(|| "{\"name\":\"item\"}".as_bytes().to_vec());
+    let request = Request::builder()
+        .uri(format!("{}/items/1", server.uri()))
+        .method(request_method.clone())
+        .body(body.clone())
+        .unwrap();
+    let response = Session::new(CurlActor::new())
+        .request(request)
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests[0].method.to_string(), request_method.as_str());
+    assert_eq!(requests[0].body, body.unwrap_or_default());
+}
+
+#[tokio::test]
+async fn test_session_post() {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) = setup_test_environment_with(method("POST"), responder).await;
+    let target_url = format!("{}/test", server.uri());
+
+    let session = Session::new(CurlActor::new());
+    let response = session
+        .post(target_url.as_str(), "test body".as_bytes().to_vec())
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // The handle of the POST request is reset for the GET request.
+    let response = session.get(target_url.as_str()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_session_concurrent_requests() {
+    let responder = MockResponder::new(ResponderType::Body("test body".as_bytes().to_vec()));
+    let (server, _tempdir) = setup_test_environment(responder).await;
+    let target_url = format!("{}/test", server.uri());
+
+    let session = Session::new(CurlActor::new());
+    let first = session.clone();
+    let second = session.clone();
+    let (first, second) = tokio::join!(
+        first.get(target_url.as_str()),
+        second.get(target_url.as_str())
+    );
+
+    assert_eq!(first.unwrap().status(), StatusCode::OK);
+    assert_eq!(second.unwrap().status(), StatusCode::OK);
+    assert_eq!(session.idle_handles(), 2);
+}
+
+/// Starts a server whose `/login` sets a session cookie that `/test` requires.
+async fn start_login_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(path("/login"))
+        .respond_with(
+            ResponseTemplate::new(StatusCode::OK.as_u16())
+                .insert_header("Set-Cookie", "session=abc123; Path=/"),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(path("/test"))
+        .and(header("cookie", "session=abc123"))
+        .respond_with(ResponseTemplate::new(StatusCode::OK.as_u16()).set_body_string("welcome"))
+        .mount(&server)
+        .await;
+    server
+}
+
+fn get_request(url: &str) -> Request<Option<Vec<u8>>> {
+    Request::builder()
+        .uri(url)
+        .method(Method::GET)
+        .body(None)
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_then_request_sends_cookies() {
+    let server = start_login_server().await;
+    let login_url = format!("{}/login", server.uri());
+    let target_url = format!("{}/test", server.uri());
+
+    let (login, next) = HttpClient::new(Collector::RamAndHeaders(Vec::new(), Vec::new()))
+        .request(get_request(login_url.as_str()))
+        .unwrap()
+        .nonblocking(CurlActor::new())
+        .then_request(get_request(target_url.as_str()))
+        .await
+        .unwrap();
+    let response = next.perform().await.unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(login.status(), StatusCode::OK);
+    assert_eq!(response.status(), StatusCode::OK);
+    // The body of the login response is not part of the next response.
+    assert_eq!(response.body().as_deref(), Some("welcome".as_bytes()));
+}
+
+#[tokio::test]
+async fn test_then_request_blocking_sends_cookies() {
+    let server = start_login_server().await;
+    let login_url = format!("{}/login", server.uri());
+    let target_url = format!("{}/test", server.uri());
+
+    let (_, next) = HttpClient::new(Collector::Ram(Vec::new()))
+        .request(get_request(login_url.as_str()))
+        .unwrap()
+        .blocking()
+        .then_request(get_request(target_url.as_str()))
+        .unwrap();
+    let response = next.perform().unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.body().as_deref(), Some("welcome".as_bytes()));
+}
+
+#[tokio::test]
+async fn test_then_request_without_cookies() {
+    let server = start_login_server().await;
+    let target_url = format!("{}/test", server.uri());
+
+    let response = HttpClient::new(Collector::Ram(Vec::new()))
+        .request(get_request(target_url.as_str()))
+        .unwrap()
+        .nonblocking(CurlActor::new())
+        .perform()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_then_request_first_request_fails() {
+    // Nothing listens on the port of a dropped listener.
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let login_url = format!("http://{}/login", listener.local_addr().unwrap());
+    drop(listener);
+
+    let result = HttpClient::new(Collector::Ram(Vec::new()))
+        .request(get_request(login_url.as_str()))
+        .unwrap()
+        .nonblocking(CurlActor::new())
+        .then_request(get_request(login_url.as_str()))
+        .await;
+
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_then_request_post_after_get_with_body() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/search"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes("results".as_bytes()))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/items"))
+        .respond_with(ResponseTemplate::new(201))
+        .mount(&server)
+        .await;
+
+    let search = Request::builder()
+        .uri(format!("{}/search", server.uri()))
+        .method(Method::GET)
+        .body(Some(r#"{"query":{"match_all":{}}}"#.as_bytes().to_vec()))
+        .unwrap();
+    let create = Request::builder()
+        .uri(format!("{}/items", server.uri()))
+        .method(Method::POST)
+        .body(Some("item".as_bytes().to_vec()))
+        .unwrap();
+
+    let (first, next) = HttpClient::new(Collector::Ram(Vec::new()))
+        .request(search)
+        .unwrap()
+        .nonblocking(CurlActor::new())
+        .then_request(create)
+        .await
+        .unwrap();
+    let response = next.perform().await.unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(first.status(), StatusCode::OK);
+    // The custom GET of the first request is not kept for the POST.
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests[1].method.to_string(), "POST");
+}
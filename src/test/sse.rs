@@ -0,0 +1,85 @@
+use async_curl::CurlActor;
+use futures::StreamExt;
+use http::{Method, Request, StatusCode};
+use url::Url;
+use wiremock::{matchers::method, ResponseTemplate};
+
+use crate::collector::StreamingCollector;
+use crate::http_client::HttpClient;
+use crate::sse::{SseDecoder, SseEvent, SseStream};
+use crate::test::test_setup::setup_test_environment_with;
+
+const SSE_STREAM: &str = ": this is a comment\n\
+    data: first\n\
+    \n\
+    event: update\n\
+    id: 42\n\
+    data: line one\n\
+    data: line two\n\
+    \n\
+    data:no space\r\n\
+    \r\n\
+    data: incomplete";
+
+fn expected_events() -> Vec<SseEvent> {
+    vec![
+        SseEvent {
+            data: "first".to_string(),
+            ..Default::default()
+        },
+        SseEvent {
+            event: Some("update".to_string()),
+            data: "line one\nline two".to_string(),
+            id: Some("42".to_string()),
+            retry: None,
+        },
+        SseEvent {
+            data: "no space".to_string(),
+            id: Some("42".to_string()),
+            ..Default::default()
+        },
+    ]
+}
+
+#[test]
+fn test_sse_decoder() {
+    let mut decoder = SseDecoder::new();
+    let mut events = Vec::new();
+    // Feed the stream in small chunks so that lines are split across them.
+    for chunk in SSE_STREAM.as_bytes().chunks(5) {
+        events.extend(decoder.decode(chunk));
+    }
+
+    assert_eq!(events, expected_events());
+}
+
+#[tokio::test]
+async fn test_sse_stream() {
+    let responder = ResponseTemplate::new(200)
+        .set_body_bytes(SSE_STREAM.as_bytes())
+        .insert_header("Content-Type", "text/event-stream");
+    let (server, _tempdir) = setup_test_environment_with(method("GET"), responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let actor = CurlActor::new();
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::GET)
+        .body(None)
+        .unwrap();
+
+    let chunks = HttpClient::new(StreamingCollector::new())
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .into_chunk_stream();
+    let mut stream = SseStream::new(chunks);
+
+    let mut events = Vec::new();
+    while let Some(event) = stream.next().await {
+        events.push(event.unwrap());
+    }
+
+    assert_eq!(stream.response().unwrap().status(), StatusCode::OK);
+    assert_eq!(events, expected_events());
+}
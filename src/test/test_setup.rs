@@ -1,11 +1,14 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
 use std::str::FromStr;
+use std::thread;
 
 use http_types::StatusCode;
 use tempfile::TempDir;
 use wiremock::{
     http::{HeaderName, HeaderValue, HeaderValues, Method},
     matchers::path,
-    Mock, MockServer, Request, Respond, ResponseTemplate,
+    Match, Mock, MockServer, Request, Respond, ResponseTemplate,
 };
 
 pub enum ResponderType {
@@ -32,17 +35,17 @@ impl Respond for MockResponder {
                     let total_file_size = mock_file.len();
                     println!("Request: {:?}", request);
                     if let Some(value) = request.headers.get(&header_name) {
-                        let offset = parse_range(value).unwrap() as usize;
+                        let (offset, end) = parse_range(value).unwrap();
+                        let offset = offset as usize;
+                        let end = end
+                            .map(|end| (end as usize).min(total_file_size - 1))
+                            .unwrap_or(total_file_size - 1);
                         println!("Offset: {}", offset);
 
-                        let content_length = format!("{}", total_file_size - offset);
+                        let body = mock_file.get(offset..=end).unwrap_or_default();
+                        let content_length = format!("{}", body.len());
                         println!("Content-Length: {}", content_length);
-                        let content_range = format!(
-                            "bytes {}-{}/{}",
-                            offset,
-                            total_file_size - 1,
-                            total_file_size
-                        );
+                        let content_range = format!("bytes {}-{}/{}", offset, end, total_file_size);
                         println!("Content-Range: {}", content_range);
 
                         ResponseTemplate::new(StatusCode::PartialContent)
@@ -58,7 +61,7 @@ impl Respond for MockResponder {
                                 HeaderName::from_str("Accept-Ranges").unwrap(),
                                 HeaderValue::from_str("bytes").unwrap(),
                             )
-                            .set_body_bytes(&mock_file[offset..])
+                            .set_body_bytes(body)
                     } else {
                         let contents = include_bytes!("sample.jpg");
                         ResponseTemplate::new(StatusCode::Ok).set_body_bytes(contents.as_slice())
@@ -69,12 +72,23 @@ impl Respond for MockResponder {
                 }
             },
             Method::Post => match &self.responder {
-                ResponderType::File => ResponseTemplate::new(StatusCode::Ok),
+                ResponderType::File => {
+                    assert_eq!(include_bytes!("sample.jpg").to_vec(), request.body);
+                    ResponseTemplate::new(StatusCode::Ok)
+                }
                 ResponderType::Body(body) => {
                     assert_eq!(*body, request.body);
                     ResponseTemplate::new(StatusCode::Ok)
                 }
             },
+            // Like many servers, the mock honors ranges without announcing them with `Accept-Ranges`.
+            Method::Head => match &self.responder {
+                ResponderType::File => ResponseTemplate::new(StatusCode::Ok)
+                    .set_body_bytes(include_bytes!("sample.jpg").as_slice()),
+                ResponderType::Body(body) => {
+                    ResponseTemplate::new(StatusCode::Ok).set_body_bytes(body.as_slice())
+                }
+            },
             Method::Put => match &self.responder {
                 ResponderType::File => {
                     assert_eq!(include_bytes!("sample.jpg").to_vec(), request.body);
@@ -92,18 +106,17 @@ impl Respond for MockResponder {
     }
 }
 
-fn parse_range(input: &HeaderValues) -> Option<u64> {
+fn parse_range(input: &HeaderValues) -> Option<(u64, Option<u64>)> {
     let input = input.to_string();
-    if let Some(start_pos) = input.find('=') {
-        if let Some(end_pos) = input.rfind('-') {
-            let numeric_value = &input[start_pos + 1..end_pos];
-            numeric_value.parse::<u64>().ok()
-        } else {
-            None
-        }
-    } else {
+    let (_, range) = input.split_once('=')?;
+    let (start, end) = range.split_once('-')?;
+    let start = start.parse::<u64>().ok()?;
+    let end = if end.is_empty() {
         None
-    }
+    } else {
+        Some(end.parse::<u64>().ok()?)
+    };
+    Some((start, end))
 }
 
 pub async fn setup_test_environment(responder: MockResponder) -> (MockServer, TempDir) {
@@ -117,3 +130,100 @@ pub async fn setup_test_environment(responder: MockResponder) -> (MockServer, Te
 
     (mock_server, tempdir)
 }
+
+/// Same as setup_test_environment but the mock only responds if the request also matches the given matcher,
+/// otherwise the mock server responds with 404 Not Found.
+pub async fn setup_test_environment_with<M, R>(matcher: M, responder: R) -> (MockServer, TempDir)
+where
+    M: Match + 'static,
+    R: Respond + 'static,
+{
+    let mock_server = MockServer::start().await;
+    let tempdir = TempDir::with_prefix_in("test", "./").unwrap();
+
+    Mock::given(path("/test"))
+        .and(matcher)
+        .respond_with(responder)
+        .mount(&mock_server)
+        .await;
+
+    (mock_server, tempdir)
+}
+
+/// Starts a proxy that only supports tunneling with CONNECT, its CONNECT response carries
+/// an `X-Proxy: connect` header.
+pub fn start_connect_proxy() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    thread::spawn(move || {
+        for client in listener.incoming() {
+            let mut client = client.unwrap();
+            thread::spawn(move || {
+                let mut reader = BufReader::new(client.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                println!("Proxy request: {:?}", request_line);
+                // CONNECT host:port HTTP/1.1
+                let target = request_line.split_whitespace().nth(1).unwrap().to_string();
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                        break;
+                    }
+                }
+
+                let mut server = TcpStream::connect(target).unwrap();
+                client
+                    .write_all(b"HTTP/1.1 200 Connection established\r\nX-Proxy: connect\r\n\r\n")
+                    .unwrap();
+
+                let mut server_reader = server.try_clone().unwrap();
+                let mut client_writer = client.try_clone().unwrap();
+                thread::spawn(move || io::copy(&mut server_reader, &mut client_writer));
+                let _ = io::copy(&mut client, &mut server);
+            });
+        }
+    });
+
+    address
+}
+
+/// Starts a server that responds to every request with the given body sent with
+/// `Transfer-Encoding: chunked`, one chunk for each slice.
+pub fn start_chunked_server(chunks: &'static [&'static [u8]]) -> SocketAddr {
+    let mut response =
+        b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n".to_vec();
+    for chunk in chunks {
+        response.extend_from_slice(format!("{:x}\r\n", chunk.len()).as_bytes());
+        response.extend_from_slice(chunk);
+        response.extend_from_slice(b"\r\n");
+    }
+    response.extend_from_slice(b"0\r\n\r\n");
+
+    start_raw_server(response)
+}
+
+/// Starts a server that responds to every request with the given bytes and closes the connection,
+/// which allows responses that the mock server cannot send, e.g. non-standard status lines.
+pub fn start_raw_server(response: Vec<u8>) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    thread::spawn(move || {
+        for client in listener.incoming() {
+            let mut client = client.unwrap();
+            let mut reader = BufReader::new(client.try_clone().unwrap());
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                    break;
+                }
+            }
+
+            client.write_all(&response).unwrap();
+        }
+    });
+
+    address
+}
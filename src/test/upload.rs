@@ -1,13 +1,26 @@
 use std::fs;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
 use async_curl::CurlActor;
+use curl::easy::{Handler, ReadError};
 use http::{Method, Request, StatusCode};
+use test_case::test_case;
 use tokio::sync::mpsc::channel;
 use url::Url;
+use wiremock::{
+    http::HeaderName,
+    matchers::{header, method},
+    ResponseTemplate,
+};
 
-use crate::collector::{Collector, FileInfo};
+use crate::collector::{Collector, ExtendedHandler, FileInfo};
+use crate::error::Error;
 use crate::http_client::{Bps, FileSize, HttpClient};
-use crate::test::test_setup::{setup_test_environment, MockResponder, ResponderType};
+use crate::test::test_setup::{
+    setup_test_environment, setup_test_environment_with, MockResponder, ResponderType,
+};
 
 #[tokio::test]
 async fn test_upload() {
@@ -192,3 +205,305 @@ async fn test_upload_sync() {
     assert_eq!(*response.body(), None);
     assert!(!response.headers().is_empty());
 }
+
+#[tokio::test]
+async fn test_upload_force_chunked() {
+    let responder = MockResponder::new(ResponderType::File);
+    let (server, tempdir) = setup_test_environment_with(
+        |request: &wiremock::Request| {
+            let headers = &request.headers;
+            !headers.contains_key(&HeaderName::from_str("content-length").unwrap())
+                && headers
+                    .get(&HeaderName::from_str("transfer-encoding").unwrap())
+                    .map(|value| value.to_string())
+                    == Some("chunked".to_string())
+        },
+        responder,
+    )
+    .await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let to_be_uploaded = tempdir.path().join("file_to_be_uploaded.jpg");
+    fs::write(to_be_uploaded.as_path(), include_bytes!("sample.jpg")).unwrap();
+    let file_size = fs::metadata(to_be_uploaded.as_path()).unwrap().len() as usize;
+
+    let actor = CurlActor::new();
+    let collector = Collector::File(FileInfo::path(to_be_uploaded));
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::PUT)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .upload_file_size(FileSize::from(file_size))
+        .unwrap()
+        .force_chunked_upload(true)
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_upload_with_atomic_rename() {
+    let (server, tempdir) =
+        setup_test_environment_with(method("PUT"), ResponseTemplate::new(200)).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let to_be_uploaded = tempdir.path().join("file_to_be_uploaded.jpg");
+    fs::write(to_be_uploaded.as_path(), include_bytes!("sample.jpg")).unwrap();
+
+    let actor = CurlActor::new();
+    let collector =
+        Collector::File(FileInfo::path(to_be_uploaded.clone()).with_atomic_rename(true));
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::PUT)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+    // The source file is not replaced by an empty temporary file.
+    assert_eq!(
+        fs::read(to_be_uploaded.as_path()).unwrap(),
+        include_bytes!("sample.jpg")
+    );
+    assert!(!FileInfo::part_path(&to_be_uploaded).exists());
+}
+
+#[tokio::test]
+async fn test_upload_aborted_by_progress_callback() {
+    let (server, tempdir) =
+        setup_test_environment_with(method("PUT"), ResponseTemplate::new(200)).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let to_be_uploaded = tempdir.path().join("file_to_be_uploaded.jpg");
+    fs::write(to_be_uploaded.as_path(), include_bytes!("sample.jpg")).unwrap();
+    let file_size = fs::metadata(to_be_uploaded.as_path()).unwrap().len() as usize;
+
+    const THRESHOLD: usize = 1_000_000;
+    let uploaded = Arc::new(AtomicUsize::new(0));
+    let progress = uploaded.clone();
+
+    let actor = CurlActor::new();
+    let collector = Collector::File(FileInfo::path(to_be_uploaded).with_progress_callback(
+        move |speed, bytes_transferred| {
+            println!("Speed: {} bytes/s", speed.as_bytes_per_sec());
+            progress.store(bytes_transferred, Ordering::SeqCst);
+            bytes_transferred < THRESHOLD
+        },
+    ));
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::PUT)
+        .body(None)
+        .unwrap();
+
+    let result = HttpClient::new(collector)
+        .upload_file_size(FileSize::from(file_size))
+        .unwrap()
+        .request(request)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await;
+
+    println!("Result: {:?}", result);
+    assert!(result.is_err());
+    let uploaded = uploaded.load(Ordering::SeqCst);
+    assert!(uploaded >= THRESHOLD);
+    assert!(uploaded < file_size);
+}
+
+#[test_case("image/jpeg", None ; "explicit type")]
+#[test_case("application/octet-stream", None ; "octet stream")]
+#[test_case("image/jpeg", Some("text/plain") ; "replaces request type")]
+#[tokio::test]
+async fn test_upload_content_type(content_type: &str, request_content_type: Option<&str>) {
+    let responder = MockResponder::new(ResponderType::File);
+    let (server, tempdir) =
+        setup_test_environment_with(header("content-type", content_type), responder).await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let to_be_uploaded = tempdir.path().join("file_to_be_uploaded.jpg");
+    fs::write(to_be_uploaded.as_path(), include_bytes!("sample.jpg")).unwrap();
+
+    let actor = CurlActor::new();
+    let collector = Collector::File(FileInfo::path(to_be_uploaded));
+    let mut request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::PUT);
+    if let Some(request_content_type) = request_content_type {
+        request = request.header("Content-Type", request_content_type);
+    }
+
+    let response = HttpClient::new(collector)
+        .request(request.body(None).unwrap())
+        .unwrap()
+        .upload_content_type(content_type)
+        .unwrap()
+        .nonblocking(actor)
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    // The mock only responds with 200 OK if it received the content type.
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[test]
+fn test_upload_content_type_invalid() {
+    let result = HttpClient::new(Collector::Ram(Vec::new())).upload_content_type("image/jpeg\r\n");
+
+    assert!(matches!(result, Err(Error::Other(_))));
+}
+
+/// Uploads the data through the read callback without knowing its size.
+#[derive(Debug)]
+struct ReadCollector(Vec<u8>);
+
+impl Handler for ReadCollector {
+    fn read(&mut self, data: &mut [u8]) -> Result<usize, ReadError> {
+        let n = data.len().min(self.0.len());
+        data[..n].copy_from_slice(&self.0[..n]);
+        self.0.drain(..n);
+        Ok(n)
+    }
+}
+
+impl ExtendedHandler for ReadCollector {}
+
+fn framed_with(request: &wiremock::Request, content_length: Option<usize>) -> bool {
+    let headers = &request.headers;
+    let sent_length = headers
+        .get(&HeaderName::from_str("content-length").unwrap())
+        .map(|value| value.to_string());
+    let chunked = headers
+        .get(&HeaderName::from_str("transfer-encoding").unwrap())
+        .map(|value| value.to_string())
+        == Some("chunked".to_string());
+    match content_length {
+        Some(length) => sent_length == Some(length.to_string()) && !chunked,
+        None => sent_length.is_none() && chunked,
+    }
+}
+
+#[test_case(Some(4096) ; "known size is sent as content length")]
+#[test_case(None ; "unknown size is sent chunked")]
+#[tokio::test]
+async fn test_post_streamed_framing(size: Option<usize>) {
+    let data = vec![b'x'; 4096];
+    let (server, _tempdir) = setup_test_environment_with(
+        move |request: &wiremock::Request| request.body.len() == 4096 && framed_with(request, size),
+        ResponseTemplate::new(200),
+    )
+    .await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::POST)
+        .body(None)
+        .unwrap();
+
+    let mut client = HttpClient::new(ReadCollector(data));
+    if let Some(size) = size {
+        client = client.upload_file_size(FileSize::from(size)).unwrap();
+    }
+    let response = client
+        .request(request)
+        .unwrap()
+        .nonblocking(CurlActor::new())
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    // The mock only responds with 200 OK if the body was framed as expected.
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_post_streamed_after_reset() {
+    let data = vec![b'x'; 4096];
+    let (server, _tempdir) = setup_test_environment_with(
+        |request: &wiremock::Request| request.body.len() == 4096 && framed_with(request, None),
+        ResponseTemplate::new(200),
+    )
+    .await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(Method::POST)
+        .body(None)
+        .unwrap();
+
+    let mut client = HttpClient::new(ReadCollector(data))
+        .upload_file_size(FileSize::from(100usize))
+        .unwrap();
+    client.reset();
+    let response = client
+        .request(request)
+        .unwrap()
+        .nonblocking(CurlActor::new())
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    // The size set before the reset is not used, the unknown size is sent chunked.
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[test_case(Method::PUT ; "put")]
+#[test_case(Method::POST ; "post")]
+#[tokio::test]
+async fn test_upload_file_sends_content_length(request_method: Method) {
+    let file_size = include_bytes!("sample.jpg").len();
+    let (server, tempdir) = setup_test_environment_with(
+        move |request: &wiremock::Request| framed_with(request, Some(file_size)),
+        ResponseTemplate::new(200),
+    )
+    .await;
+    let target_url = Url::parse(format!("{}/test", server.uri()).as_str()).unwrap();
+
+    let to_be_uploaded = tempdir.path().join("file_to_be_uploaded.jpg");
+    fs::write(to_be_uploaded.as_path(), include_bytes!("sample.jpg")).unwrap();
+
+    // The size is not set, it is taken from the file.
+    let collector = Collector::File(FileInfo::path(to_be_uploaded));
+    let request = Request::builder()
+        .uri(target_url.as_str())
+        .method(request_method)
+        .body(None)
+        .unwrap();
+
+    let response = HttpClient::new(collector)
+        .request(request)
+        .unwrap()
+        .nonblocking(CurlActor::new())
+        .perform()
+        .await
+        .unwrap();
+
+    println!("Response: {:?}", response);
+    assert_eq!(response.status(), StatusCode::OK);
+}